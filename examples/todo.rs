@@ -159,11 +159,12 @@ impl Component for Todo {
             };
             let cursor = self.cursor.clone();
             Input::with(InputProperties {
-                style,
+                style: Some(style),
                 content: Rope::from_str(content),
                 cursor,
                 on_change: self.handle_input_change.clone().into(),
-                focused: true,
+                // Only mounted while editing, when it takes all typed keys
+                focused: Some(true),
             })
         } else {
             Text::with(
@@ -382,7 +383,7 @@ impl Component for TodoMvc {
         let todo_items = Item::auto(Select::with_key(
             "select",
             SelectProperties {
-                background: theme.unchecked,
+                background: Some(theme.unchecked),
                 direction: FlexDirection::Column,
                 num_items: todos.len(),
                 selected: focus_index,
@@ -417,7 +418,7 @@ impl Component for TodoMvc {
                     .into()
                 },
                 item_size: 1,
-                focused: true,
+                focused: None,
                 on_change: Some(link.callback(Message::FocusItem)),
             },
         ));
@@ -9,13 +9,14 @@ pub use self::error::{Error, Result};
 use crossterm::{self, queue, QueueableCommand};
 use futures::stream::{Stream, StreamExt};
 use std::{
+    env,
     io::{self, BufWriter, Stdout, Write},
     pin::Pin,
     time::{Duration, Instant},
 };
 use tokio::{
     self,
-    runtime::{Builder as RuntimeBuilder, Runtime},
+    runtime::{Builder as RuntimeBuilder, Handle, Runtime},
     sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
 };
 
@@ -24,9 +25,13 @@ use self::{
     utils::MeteredWriter,
 };
 use zi::{
-    app::{App, ComponentMessage, MessageSender},
-    terminal::{Canvas, Colour, KeyCode, KeyEvent, KeyModifiers, Size, Style},
-    Layout,
+    app::{App, BlockingTask, ComponentMessage, MessageSender, Task, TaskSpawner},
+    store::Store,
+    terminal::{
+        Canvas, Colour, ColourSupport, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind, Position, Size, Style, UnderlineStyle,
+    },
+    KeymapOverrides, Layout, Theme,
 };
 
 /// Creates a new backend with an incremental painter. It only draws those
@@ -75,6 +80,12 @@ pub struct Crossterm<PainterT: Painter = IncrementalPainter> {
     painter: PainterT,
     events: Option<EventStream>,
     link: LinkChannel,
+    keymap_overrides: Option<KeymapOverrides>,
+    escape_timeout: Option<Duration>,
+    macros_enabled: bool,
+    theme: Option<Theme>,
+    store: Option<Store>,
+    colour_support: ColourSupport,
 }
 
 impl<PainterT: Painter> Crossterm<PainterT> {
@@ -92,17 +103,78 @@ impl<PainterT: Painter> Crossterm<PainterT> {
             ),
             events: Some(new_event_stream()),
             link: LinkChannel::new(),
+            keymap_overrides: None,
+            escape_timeout: None,
+            macros_enabled: false,
+            theme: None,
+            store: None,
+            colour_support: detect_colour_support(),
         };
-        initialise_tty::<PainterT, _>(&mut backend.target)?;
+        initialise_tty::<PainterT, _>(&mut backend.target, backend.colour_support)?;
         Ok(backend)
     }
 
+    /// Sets key bindings which replace the default bindings of components,
+    /// e.g. loaded from a user's configuration file with
+    /// [`KeymapOverrides::load`](../zi/struct.KeymapOverrides.html#method.load).
+    /// The overrides are used by all subsequent calls to `run_event_loop`.
+    pub fn set_keymap_overrides(&mut self, overrides: KeymapOverrides) -> &mut Self {
+        self.keymap_overrides = Some(overrides);
+        self
+    }
+
+    /// Sets how long to wait for another key after a bare `Esc` before
+    /// delivering it, see
+    /// [`App::set_escape_timeout`](../zi/app/struct.App.html#method.set_escape_timeout).
+    /// Useful on slow connections, where the bytes of an `Alt` chord may
+    /// arrive separately.
+    pub fn set_escape_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.escape_timeout = timeout;
+        self
+    }
+
+    /// Enables keyboard macros, see
+    /// [`App::set_macros_enabled`](../zi/app/struct.App.html#method.set_macros_enabled).
+    pub fn set_macros_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.macros_enabled = enabled;
+        self
+    }
+
+    /// Sets the theme the application starts with, see
+    /// [`App::set_theme`](../zi/app/struct.App.html#method.set_theme).
+    pub fn set_theme(&mut self, theme: Theme) -> &mut Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Sets the application's store, see
+    /// [`App::set_store`](../zi/app/struct.App.html#method.set_store). The
+    /// store is moved into the application by the next call to
+    /// `run_event_loop`.
+    pub fn set_store(&mut self, store: Store) -> &mut Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Overrides the colour support detected from the environment, see
+    /// [`detect_colour_support`](fn.detect_colour_support.html). Colours the
+    /// terminal can't display are replaced by the nearest one it can.
+    pub fn set_colour_support(&mut self, colour_support: ColourSupport) -> &mut Self {
+        self.colour_support = colour_support;
+        self
+    }
+
     /// Starts the event loop. This is the main entry point of a Zi application.
     /// It draws and presents the components to the backend, handles user input
     /// and delivers messages to components. This method returns either when
     /// prompted using the [`exit`](struct.ComponentLink.html#method.exit)
     /// method on [`ComponentLink`](struct.ComponentLink.html) or on error.
     ///
+    /// Tasks spawned by components with
+    /// [`ComponentLink::spawn`](../zi/struct.ComponentLink.html#method.spawn)
+    /// run on the event loop's tokio runtime. Those still running when the
+    /// event loop returns are cancelled.
+    ///
     /// ```no_run
     /// # use zi::prelude::*;
     /// # use zi::components::text::{Text, TextProperties};
@@ -118,7 +190,26 @@ impl<PainterT: Painter> Crossterm<PainterT> {
             self.size()?,
             layout,
         );
+        app.set_task_spawner(TokioTaskSpawner(tokio_runtime.handle().clone()));
+        if let Some(ref overrides) = self.keymap_overrides {
+            app.set_keymap_overrides(overrides.clone());
+        }
+        app.set_escape_timeout(self.escape_timeout);
+        app.set_macros_enabled(self.macros_enabled);
+        if let Some(ref theme) = self.theme {
+            app.set_theme(theme.clone());
+        }
+        if let Some(store) = self.store.take() {
+            app.set_store(store);
+        }
+
+        let result = self.draw_until_exit(&mut tokio_runtime, &mut app);
+        // Don't wait for blocking tasks which are still running
+        tokio_runtime.shutdown_background();
+        result
+    }
 
+    fn draw_until_exit(&mut self, tokio_runtime: &mut Runtime, app: &mut App) -> Result<()> {
         while !app.poll_state().exit() {
             let canvas = app.draw();
 
@@ -132,7 +223,7 @@ impl<PainterT: Painter> Crossterm<PainterT> {
                 num_bytes_presented,
             );
 
-            self.poll_events_batch(&mut tokio_runtime, &mut app, last_drawn)?;
+            self.poll_events_batch(tokio_runtime, app, last_drawn)?;
         }
 
         Ok(())
@@ -163,7 +254,7 @@ impl<PainterT: Painter> Crossterm<PainterT> {
     pub fn resume(&mut self) -> Result<()> {
         self.painter = PainterT::create(self.size()?);
         self.events = Some(new_event_stream());
-        initialise_tty::<PainterT, _>(&mut self.target)
+        initialise_tty::<PainterT, _>(&mut self.target, self.colour_support)
     }
 
     /// Poll as many events as we can respecting REDRAW_LATENCY and REDRAW_LATENCY_SUSTAINED_IO
@@ -183,6 +274,20 @@ impl<PainterT: Painter> Crossterm<PainterT> {
         let mut first_event_time: Option<Instant> = None;
 
         while !force_redraw && !app.poll_state().exit() {
+            // Keys replayed from a macro or a paste are handled one at a time,
+            // after the messages sent while handling the previous key and a
+            // redraw
+            if app.has_queued_input() {
+                while let Ok(link_message) = link.receiver.try_recv() {
+                    app.handle_message(link_message);
+                }
+                if !app.poll_state().dirty() {
+                    app.handle_queued_input();
+                }
+                force_redraw = app.poll_state().dirty();
+                continue;
+            }
+
             let timeout_duration = {
                 let since_last_drawn = last_drawn.elapsed();
                 if app.poll_state().dirty() && since_last_drawn >= REDRAW_LATENCY {
@@ -193,6 +298,7 @@ impl<PainterT: Painter> Crossterm<PainterT> {
                     Duration::from_millis(if app.is_tickable() { 60 } else { 60_000 })
                 }
             };
+            let input_deadline = app.next_deadline();
             (runtime.block_on(async {
                 tokio::select! {
                     link_message = link.receiver.recv() => {
@@ -214,6 +320,10 @@ impl<PainterT: Painter> Crossterm<PainterT> {
                                 || app.poll_state().resized());
                         Ok(())
                     }
+                    _ = sleep_until(input_deadline) => {
+                        app.handle_timeout(Instant::now());
+                        Ok(())
+                    }
                     _ = tokio::time::sleep(timeout_duration) => {
                         app.tick();
                         force_redraw = true;
@@ -239,6 +349,7 @@ impl<PainterT: Painter> Crossterm<PainterT> {
         let Self {
             ref mut target,
             ref mut painter,
+            colour_support,
             ..
         } = *self;
         let initial_num_bytes_written = target.num_bytes_written();
@@ -247,7 +358,7 @@ impl<PainterT: Painter> Crossterm<PainterT> {
                 PaintOperation::WriteContent(grapheme) => {
                     queue!(target, crossterm::style::Print(grapheme))?
                 }
-                PaintOperation::SetStyle(style) => queue_set_style(target, style)?,
+                PaintOperation::SetStyle(style) => queue_set_style(target, style, colour_support)?,
                 PaintOperation::MoveTo(position) => queue!(
                     target,
                     crossterm::cursor::MoveTo(position.x as u16, position.y as u16)
@@ -260,6 +371,74 @@ impl<PainterT: Painter> Crossterm<PainterT> {
     }
 }
 
+/// Detects how many colours the terminal supports from the environment.
+///
+/// `NO_COLOR` (see <https://no-color.org>) turns colours off and
+/// `COLORTERM=truecolor` or `24bit` enables 24-bit colours. Otherwise `TERM`
+/// is used: `dumb` terminals get no colours, those ending in `-direct` get
+/// 24-bit colours and the consoles and terminal types known to be limited to
+/// 8 or 16 colours (`linux`, `vt100`, `xterm-color`, `*-16color`, ...) get
+/// the 16 ANSI colours. All other terminals, including plain `xterm`, get the
+/// 256-colour palette, as `COLORTERM` is often lost, e.g. over SSH. Without
+/// `TERM`, e.g. on Windows, 24-bit colours are assumed.
+///
+/// The result and the variables it was detected from are logged at the info
+/// level.
+pub fn detect_colour_support() -> ColourSupport {
+    let var = |name| env::var(name).ok().filter(|value| !value.is_empty());
+    let (no_color, colorterm, term) = (var("NO_COLOR"), var("COLORTERM"), var("TERM"));
+
+    let colour_support =
+        colour_support_from_env(no_color.as_deref(), colorterm.as_deref(), term.as_deref());
+    log::info!(
+        "Colour support: {:?} (NO_COLOR={:?} COLORTERM={:?} TERM={:?})",
+        colour_support,
+        no_color,
+        colorterm,
+        term,
+    );
+    colour_support
+}
+
+/// Decides the colour support from the values of `NO_COLOR`, `COLORTERM` and
+/// `TERM`, with empty variables given as `None`. See
+/// [`detect_colour_support`](fn.detect_colour_support.html) for the rules.
+fn colour_support_from_env(
+    no_color: Option<&str>,
+    colorterm: Option<&str>,
+    term: Option<&str>,
+) -> ColourSupport {
+    if no_color.is_some() {
+        return ColourSupport::Monochrome;
+    }
+    if let Some("truecolor" | "24bit") = colorterm {
+        return ColourSupport::TrueColour;
+    }
+    match term {
+        None => ColourSupport::TrueColour,
+        Some("dumb") => ColourSupport::Monochrome,
+        Some(term) if term.ends_with("-direct") => ColourSupport::TrueColour,
+        Some("linux" | "ansi" | "cons25") => ColourSupport::Ansi16,
+        Some(term)
+            if term.starts_with("vt")
+                || term.ends_with("-color")
+                || term.ends_with("-8color")
+                || term.ends_with("-16color") =>
+        {
+            ColourSupport::Ansi16
+        }
+        Some(_) => ColourSupport::Ansi256,
+    }
+}
+
+/// Sleeps until the deadline, or forever if there is none.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => futures::future::pending().await,
+    }
+}
+
 impl<PainterT: Painter> Drop for Crossterm<PainterT> {
     fn drop(&mut self) {
         queue!(
@@ -267,6 +446,8 @@ impl<PainterT: Painter> Drop for Crossterm<PainterT> {
             crossterm::style::ResetColor,
             crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
             crossterm::cursor::Show,
+            crossterm::event::DisableMouseCapture,
+            crossterm::event::DisableBracketedPaste,
             crossterm::terminal::LeaveAlternateScreen
         )
         .expect("Failed to clear screen when closing `crossterm` backend");
@@ -309,70 +490,139 @@ impl MessageSender for UnboundedMessageSender {
     }
 }
 
+#[derive(Debug)]
+struct TokioTaskSpawner(Handle);
+
+impl TaskSpawner for TokioTaskSpawner {
+    fn spawn(&self, task: Task) {
+        self.0.spawn(task);
+    }
+
+    fn spawn_blocking(&self, task: BlockingTask) {
+        self.0.spawn_blocking(task);
+    }
+}
+
 #[inline]
-fn initialise_tty<PainterT: Painter, TargetT: Write>(target: &mut TargetT) -> Result<()> {
+fn initialise_tty<PainterT: Painter, TargetT: Write>(
+    target: &mut TargetT,
+    colour_support: ColourSupport,
+) -> Result<()> {
     target
         .queue(crossterm::terminal::EnterAlternateScreen)?
-        .queue(crossterm::cursor::Hide)?;
+        .queue(crossterm::cursor::Hide)?
+        .queue(crossterm::event::EnableMouseCapture)?
+        .queue(crossterm::event::EnableBracketedPaste)?;
     crossterm::terminal::enable_raw_mode()?;
-    queue_set_style(target, &PainterT::INITIAL_STYLE)?;
+    queue_set_style(target, &PainterT::INITIAL_STYLE, colour_support)?;
     target.flush()?;
     Ok(())
 }
 
 #[inline]
-fn queue_set_style(target: &mut impl Write, style: &Style) -> Result<()> {
-    use crossterm::style::{
-        Attribute, Color, SetAttribute, SetBackgroundColor, SetForegroundColor,
-    };
-
-    // Bold
-    if style.bold {
-        queue!(target, SetAttribute(Attribute::Bold))?;
-    } else {
-        // Using Reset is not ideal as it resets all style attributes. The correct thing to do
-        // would be to use `NoBold`, but it seems this is not reliably supported (at least it
-        // didn't work for me in tmux, although it does in alacritty).
-        // Also see https://github.com/crossterm-rs/crossterm/issues/294
-        queue!(target, SetAttribute(Attribute::Reset))?;
+fn queue_set_style(
+    target: &mut impl Write,
+    style: &Style,
+    colour_support: ColourSupport,
+) -> Result<()> {
+    use crossterm::style::{Attribute, SetAttribute, SetUnderlineColor};
+
+    let style = style.downsample(colour_support);
+
+    // Using Reset is not ideal as it resets all style attributes. The correct thing to do
+    // would be to turn off individual attributes, e.g. with `NoBold`, but it seems this is
+    // not reliably supported (at least it didn't work for me in tmux, although it does in
+    // alacritty). Also see https://github.com/crossterm-rs/crossterm/issues/294
+    queue!(target, SetAttribute(Attribute::Reset))?;
+
+    let attributes = [
+        (style.bold, Attribute::Bold),
+        (style.dim, Attribute::Dim),
+        (style.italic, Attribute::Italic),
+        (style.blink, Attribute::SlowBlink),
+        (style.reverse, Attribute::Reverse),
+        (style.strikethrough, Attribute::CrossedOut),
+    ];
+    for &(enabled, attribute) in &attributes[..] {
+        if enabled {
+            queue!(target, SetAttribute(attribute))?;
+        }
     }
 
     // Underline
     if style.underline {
-        queue!(target, SetAttribute(Attribute::Underlined))?;
-    } else {
-        queue!(target, SetAttribute(Attribute::NoUnderline))?;
+        let attribute = match style.underline_style {
+            UnderlineStyle::Single => Attribute::Underlined,
+            UnderlineStyle::Double => Attribute::DoubleUnderlined,
+            UnderlineStyle::Curly => Attribute::Undercurled,
+            UnderlineStyle::Dotted => Attribute::Underdotted,
+            UnderlineStyle::Dashed => Attribute::Underdashed,
+        };
+        queue!(target, SetAttribute(attribute))?;
+        if style.underline_colour != Colour::Default {
+            queue!(
+                target,
+                SetUnderlineColor(map_colour(style.underline_colour))
+            )?;
+        }
     }
 
-    // Background
-    {
-        let Colour { red, green, blue } = style.background;
-        queue!(
-            target,
-            SetBackgroundColor(Color::Rgb {
-                r: red,
-                g: green,
-                b: blue
-            })
-        )?;
-    }
+    queue_set_colour(target, style.background, true)?;
+    queue_set_colour(target, style.foreground, false)?;
 
-    // Foreground
-    {
-        let Colour { red, green, blue } = style.foreground;
-        queue!(
-            target,
-            SetForegroundColor(Color::Rgb {
-                r: red,
-                g: green,
-                b: blue
-            })
-        )?;
+    Ok(())
+}
+
+/// Returns the SGR code that sets the ANSI colour with the given index (0-15)
+/// as the background or foreground colour: 30-37 and 90-97 for the normal and
+/// bright foreground colours, 10 more for the background.
+#[inline]
+fn ansi_sgr_code(index: u8, background: bool) -> u8 {
+    let code = if index < 8 { 30 + index } else { 82 + index };
+    if background {
+        code + 10
+    } else {
+        code
     }
+}
 
+/// Sets the background or foreground colour. The ANSI colours are set with
+/// their own SGR codes rather than as entries of the 256-colour palette, as
+/// terminals with only 16 colours don't understand the latter.
+#[inline]
+fn queue_set_colour(target: &mut impl Write, colour: Colour, background: bool) -> Result<()> {
+    use crossterm::style::{SetBackgroundColor, SetForegroundColor};
+
+    match colour {
+        Colour::Ansi(colour) => {
+            write!(
+                target,
+                "\x1b[{}m",
+                ansi_sgr_code(colour.index(), background)
+            )?;
+        }
+        _ if background => queue!(target, SetBackgroundColor(map_colour(colour)))?,
+        _ => queue!(target, SetForegroundColor(map_colour(colour)))?,
+    }
     Ok(())
 }
 
+#[inline]
+fn map_colour(colour: Colour) -> crossterm::style::Color {
+    use crossterm::style::Color;
+
+    match colour {
+        Colour::Default => Color::Reset,
+        Colour::Ansi(colour) => Color::AnsiValue(colour.index()),
+        Colour::Indexed(index) => Color::AnsiValue(index),
+        Colour::Rgb { red, green, blue } => Color::Rgb {
+            r: red,
+            g: green,
+            b: blue,
+        },
+    }
+}
+
 enum FilteredEvent {
     Input(zi::terminal::Event),
     Resize(Size),
@@ -386,12 +636,22 @@ fn new_event_stream() -> EventStream {
         crossterm::event::EventStream::new()
             .filter_map(|event| async move {
                 match event {
-                    Ok(crossterm::event::Event::Key(key_event)) => Some(Ok(FilteredEvent::Input(
-                        zi::terminal::Event::Key(map_event(key_event)),
-                    ))),
+                    Ok(crossterm::event::Event::Key(key_event)) => {
+                        map_event(key_event).map(|key_event| {
+                            Ok(FilteredEvent::Input(zi::terminal::Event::Key(key_event)))
+                        })
+                    }
+                    Ok(crossterm::event::Event::Mouse(mouse_event)) => {
+                        Some(Ok(FilteredEvent::Input(zi::terminal::Event::Mouse(
+                            map_mouse_event(mouse_event),
+                        ))))
+                    }
                     Ok(crossterm::event::Event::Resize(width, height)) => Some(Ok(
                         FilteredEvent::Resize(Size::new(width as usize, height as usize)),
                     )),
+                    Ok(crossterm::event::Event::Paste(text)) => {
+                        Some(Ok(FilteredEvent::Input(zi::terminal::Event::Paste(text))))
+                    }
                     Ok(_) => None,
                     Err(error) => Some(Err(error.into())),
                 }
@@ -401,7 +661,11 @@ fn new_event_stream() -> EventStream {
 }
 
 #[inline]
-fn map_event(key: crossterm::event::KeyEvent) -> KeyEvent {
+fn map_event(key: crossterm::event::KeyEvent) -> Option<KeyEvent> {
+    if key.kind == crossterm::event::KeyEventKind::Release {
+        return None;
+    }
+
     let key_code = match key.code {
         crossterm::event::KeyCode::Backspace => KeyCode::Backspace,
         crossterm::event::KeyCode::Left => KeyCode::Left,
@@ -421,8 +685,83 @@ fn map_event(key: crossterm::event::KeyEvent) -> KeyEvent {
         crossterm::event::KeyCode::Char(char) => KeyCode::Char(char),
         crossterm::event::KeyCode::Enter => KeyCode::Enter,
         crossterm::event::KeyCode::Tab => KeyCode::Tab,
+        _ => return None,
+    };
+
+    let modifiers = KeyModifiers::from_bits_truncate(key.modifiers.bits());
+    Some(KeyEvent::new(key_code, modifiers))
+}
+
+#[inline]
+fn map_mouse_event(mouse: crossterm::event::MouseEvent) -> MouseEvent {
+    let map_button = |button| match button {
+        crossterm::event::MouseButton::Left => MouseButton::Left,
+        crossterm::event::MouseButton::Right => MouseButton::Right,
+        crossterm::event::MouseButton::Middle => MouseButton::Middle,
+    };
+    let kind = match mouse.kind {
+        crossterm::event::MouseEventKind::Down(button) => MouseEventKind::Down(map_button(button)),
+        crossterm::event::MouseEventKind::Up(button) => MouseEventKind::Up(map_button(button)),
+        crossterm::event::MouseEventKind::Drag(button) => MouseEventKind::Drag(map_button(button)),
+        crossterm::event::MouseEventKind::Moved => MouseEventKind::Moved,
+        crossterm::event::MouseEventKind::ScrollDown => MouseEventKind::ScrollDown,
+        crossterm::event::MouseEventKind::ScrollUp => MouseEventKind::ScrollUp,
     };
 
-    let modifiers = KeyModifiers::from_bits(key.modifiers.bits()).unwrap_or(KeyModifiers::NONE);
-    KeyEvent::new(key_code, modifiers)
+    let modifiers = KeyModifiers::from_bits_truncate(mouse.modifiers.bits());
+    MouseEvent::new(
+        kind,
+        Position::new(mouse.column as usize, mouse.row as usize),
+        modifiers,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colour_support_honours_no_color_and_colorterm() {
+        assert_eq!(
+            colour_support_from_env(Some("1"), Some("truecolor"), Some("xterm-direct")),
+            ColourSupport::Monochrome
+        );
+        for colorterm in ["truecolor", "24bit"] {
+            assert_eq!(
+                colour_support_from_env(None, Some(colorterm), Some("linux")),
+                ColourSupport::TrueColour
+            );
+        }
+        assert_eq!(
+            colour_support_from_env(None, Some("yes"), Some("xterm-256color")),
+            ColourSupport::Ansi256
+        );
+    }
+
+    #[test]
+    fn colour_support_from_term() {
+        let from_term = |term| colour_support_from_env(None, None, term);
+        assert_eq!(from_term(None), ColourSupport::TrueColour);
+        assert_eq!(from_term(Some("dumb")), ColourSupport::Monochrome);
+        assert_eq!(from_term(Some("xterm-direct")), ColourSupport::TrueColour);
+        assert_eq!(from_term(Some("xterm-256color")), ColourSupport::Ansi256);
+        assert_eq!(from_term(Some("tmux-256color")), ColourSupport::Ansi256);
+        for term in ["linux", "vt100", "ansi", "xterm-color", "rxvt-16color"] {
+            assert_eq!(from_term(Some(term)), ColourSupport::Ansi16, "{}", term);
+        }
+        // `COLORTERM` is often lost over SSH, so unknown terminals aren't
+        // limited to 16 colours.
+        for term in ["xterm", "screen", "alacritty", "xterm-kitty"] {
+            assert_eq!(from_term(Some(term)), ColourSupport::Ansi256, "{}", term);
+        }
+    }
+
+    #[test]
+    fn ansi_sgr_codes() {
+        let codes = |index| (ansi_sgr_code(index, false), ansi_sgr_code(index, true));
+        assert_eq!(codes(0), (30, 40));
+        assert_eq!(codes(7), (37, 47));
+        assert_eq!(codes(8), (90, 100));
+        assert_eq!(codes(15), (97, 107));
+    }
 }
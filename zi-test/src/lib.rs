@@ -0,0 +1,136 @@
+//! A headless backend for [Zi](https://docs.rs/zi) meant for end-to-end tests.
+//!
+//! This crate re-exports the `testing` module of zi, which is only compiled
+//! with zi's `testing` feature.
+//!
+//! [`TestBackend`](struct.TestBackend.html) owns an [`App`](../zi/app/struct.App.html)
+//! and renders it to an in-memory canvas of a fixed size. Tests script key
+//! presses, resizes and ticks, the backend delivers any messages sent by
+//! components until the application settles, and the drawn canvas can then
+//! be inspected. Tasks spawned by components are run on the test thread while
+//! the application settles.
+//!
+//! ```
+//! # use zi::{components::text::{Text, TextProperties}, prelude::*};
+//! # use zi_test::TestBackend;
+//! let mut backend = TestBackend::new(
+//!     Size::new(5, 1),
+//!     Text::with(TextProperties::new().content("Hello")),
+//! );
+//! backend.assert_lines(&["Hello"]);
+//! ```
+pub use zi::testing::{snapshot, TestBackend};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zi::{
+        components::text::{Text, TextProperties},
+        prelude::*,
+    };
+
+    struct Counter {
+        count: usize,
+        link: ComponentLink<Self>,
+    }
+
+    #[derive(Debug)]
+    enum Message {
+        Increment,
+        Forward,
+        Exit,
+    }
+
+    impl Component for Counter {
+        type Message = Message;
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self { count: 0, link }
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                Message::Increment => {
+                    self.count += 1;
+                    ShouldRender::Yes
+                }
+                Message::Forward => {
+                    self.link.send(Message::Increment);
+                    ShouldRender::No
+                }
+                Message::Exit => {
+                    self.link.exit();
+                    ShouldRender::No
+                }
+            }
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(format!("count={}", self.count)))
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("increment", [KeyEvent::from(KeyCode::Char('+'))], || {
+                Message::Increment
+            });
+            bindings.add("forward", [KeyEvent::from(KeyCode::Char('>'))], || {
+                Message::Forward
+            });
+            bindings.add("exit", [KeyEvent::from(KeyCode::Esc)], || Message::Exit);
+        }
+
+        fn tick(&self) -> Option<Self::Message> {
+            Some(Message::Increment)
+        }
+    }
+
+    #[test]
+    fn scripted_input_is_rendered() {
+        let mut backend = TestBackend::new(Size::new(10, 2), Counter::with(()));
+        backend.assert_lines(&["count=0", ""]);
+
+        backend.keys([KeyCode::Char('+'), KeyCode::Char('+')]);
+        backend.assert_lines(&["count=2", ""]);
+
+        // Messages sent through a `ComponentLink` are delivered before returning
+        backend.key(KeyCode::Char('>'));
+        backend.assert_lines(&["count=3", ""]);
+
+        backend.tick().tick();
+        backend.assert_lines(&["count=5", ""]);
+
+        backend.resize(Size::new(4, 1));
+        assert_eq!(backend.size(), Size::new(4, 1));
+        backend.assert_lines(&["coun"]);
+
+        backend.key(KeyCode::Esc);
+        assert!(backend.has_exited());
+        backend.key(KeyCode::Char('+'));
+        backend.assert_lines(&["coun"]);
+    }
+
+    #[test]
+    fn unkeyed_siblings_of_the_same_type_are_distinct() {
+        let backend = TestBackend::new(
+            Size::new(4, 1),
+            Layout::row([
+                Item::auto(Text::with(TextProperties::new().content("ab"))),
+                Item::auto(Text::with(TextProperties::new().content("cd"))),
+            ]),
+        );
+        backend.assert_lines(&["abcd"]);
+        assert_eq!(backend.style(0, 0), Some(Style::default()));
+        backend.assert_snapshot(
+            "canvas 4x1\n\
+             |abcd|\n\
+             styles\n\
+             |aaaa|\n\
+             a: bg=#000000 fg=#ffffff\n",
+        );
+    }
+}
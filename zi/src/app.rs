@@ -3,16 +3,32 @@
 //! end application.
 
 use smallvec::SmallVec;
-use std::{collections::HashMap, fmt::Debug, time::Instant};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
     component::{
-        bindings::{BindingQuery, DynamicBindings, KeySequenceSlice, NamedBindingQuery},
-        layout::{LaidCanvas, LaidComponent, Layout},
+        bindings::{
+            BindingQuery, DynamicBindings, KeyPattern, KeySequenceSlice, NamedBindingQuery,
+        },
+        context::{ContextMap, SharedContexts},
+        layout::{Clip, LaidCanvas, LaidComponent, Layout},
+        overrides::KeymapOverrides,
+        tasks::{ComponentTasks, SharedSpawner},
         template::{ComponentId, DynamicMessage, DynamicProperties, Renderable},
-        LinkMessage, ShouldRender,
+        ComponentRuntime, FocusRequest, LinkMessage, MacroRequest, ShouldRender,
     },
-    terminal::{Canvas, Event, KeyCode, KeyEvent, KeyModifiers, Position, Rect, Size},
+    macros::Macros,
+    store::{SharedStore, State, Store},
+    terminal::{Canvas, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, Position, Rect, Size},
+    theme::{SharedTheme, Theme},
 };
 
 pub trait MessageSender: Debug + Send + 'static {
@@ -24,6 +40,29 @@ pub trait MessageSender: Debug + Send + 'static {
 #[derive(Debug)]
 pub struct ComponentMessage(pub(crate) LinkMessage);
 
+/// A task spawned by a component with
+/// [`ComponentLink::spawn`](../struct.ComponentLink.html#method.spawn).
+pub type Task = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// A blocking task spawned by a component with
+/// [`ComponentLink::spawn_blocking`](../struct.ComponentLink.html#method.spawn_blocking).
+pub type BlockingTask = Box<dyn FnOnce() + Send + 'static>;
+
+/// Runs the tasks spawned by components, typically on the async runtime of a
+/// backend. Tasks deliver their result as a message using the app's
+/// [`MessageSender`](trait.MessageSender.html).
+///
+/// Tasks of unmounted components are cancelled by the app: a cancelled
+/// `Task` is woken and completes on its next poll, without polling the
+/// component's future again, so the spawner doesn't need to track them.
+pub trait TaskSpawner: Debug + Send + Sync + 'static {
+    /// Runs a task to completion.
+    fn spawn(&self, task: Task);
+
+    /// Runs a task which may block, on a thread where blocking is acceptable.
+    fn spawn_blocking(&self, task: BlockingTask);
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum PollState {
     Clean,
@@ -70,7 +109,19 @@ pub struct App {
     components: HashMap<ComponentId, MountedComponent>,
     layouts: HashMap<ComponentId, Layout>,
     subscriptions: ComponentSubscriptions,
+    focus: FocusChain,
     controller: InputController,
+    pending_sequence: Option<PendingSequence>,
+    escape_timeout: Option<Duration>,
+    pending_escape: Option<Instant>,
+    /// The keys of pasted text which no paste handler accepted, typed one at
+    /// a time like the keys of a macro.
+    pasted_keys: VecDeque<KeyEvent>,
+    keymap_overrides: Option<KeymapOverrides>,
+    macros: Option<Macros>,
+    theme: SharedTheme,
+    store: SharedStore,
+    spawner: SharedSpawner,
     runtime: AppRuntime,
     sender: Box<dyn MessageSender>,
 }
@@ -136,7 +187,17 @@ impl App {
             components: HashMap::new(),
             layouts: HashMap::new(),
             subscriptions: ComponentSubscriptions::new(),
+            focus: FocusChain::new(),
             controller: InputController::new(),
+            pending_sequence: None,
+            escape_timeout: None,
+            pending_escape: None,
+            pasted_keys: VecDeque::new(),
+            keymap_overrides: None,
+            macros: None,
+            theme: SharedTheme::default(),
+            store: SharedStore::default(),
+            spawner: SharedSpawner::default(),
             runtime: AppRuntime::new(size),
             sender: Box::new(sender),
         }
@@ -148,6 +209,203 @@ impl App {
         self.runtime.poll_state
     }
 
+    /// Sets key bindings which replace the default bindings of components.
+    ///
+    /// Overrides are applied to components as they are mounted, as well as
+    /// to all components that are already mounted. They are meant to be set
+    /// once, before the application starts, e.g. from a user's configuration
+    /// file. See [`KeymapOverrides`](../struct.KeymapOverrides.html).
+    pub fn set_keymap_overrides(&mut self, overrides: KeymapOverrides) {
+        for (component_id, component) in self.components.iter_mut() {
+            overrides.apply(component_id.type_name(), component.bindings.keymap_mut());
+        }
+        self.keymap_overrides = Some(overrides);
+    }
+
+    /// Returns the active theme, see the [`theme`](../theme/index.html)
+    /// module. The default is [`Theme::dark`](../theme/struct.Theme.html#method.dark).
+    pub fn theme(&self) -> Arc<Theme> {
+        self.theme.get()
+    }
+
+    /// Replaces the active theme. All mounted components are re-rendered, so
+    /// they pick up their styles from the new theme.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme.set(theme);
+        for component in self.components.values_mut() {
+            component.should_render = true;
+        }
+        self.runtime.poll_state.merge(PollState::Dirty(None));
+    }
+
+    /// Installs the application's store, see the [`store`](../store/index.html)
+    /// module. A store installed earlier is replaced, together with all its
+    /// subscriptions, so this is meant to be called before the application
+    /// starts.
+    pub fn set_store(&mut self, store: Store) {
+        self.store.set(store);
+    }
+
+    /// Sets the spawner which runs the tasks spawned by components, see
+    /// [`ComponentLink::spawn`](../struct.ComponentLink.html#method.spawn).
+    /// Without a spawner, spawned tasks are dropped.
+    pub fn set_task_spawner(&mut self, spawner: impl TaskSpawner) {
+        self.spawner.set(Arc::new(spawner));
+    }
+
+    /// Returns a slice of the store's state, or `None` if there is no store
+    /// holding a `StateT`.
+    pub fn select<StateT: State, SliceT>(
+        &self,
+        selector: impl FnOnce(&StateT) -> SliceT,
+    ) -> Option<SliceT> {
+        self.store
+            .read(|store| store.select(selector))
+            .and_then(|slice| slice)
+    }
+
+    /// Sets how long to wait for another key after a bare `Esc`.
+    ///
+    /// Terminals send `Alt` chords as `Esc` followed by the key, which on slow
+    /// connections may arrive separately. With a timeout, a key that follows
+    /// `Esc` before the timeout expires is delivered as an `Alt` chord,
+    /// otherwise `Esc` is delivered on its own once the timeout expires. `None`
+    /// (the default) delivers `Esc` immediately.
+    pub fn set_escape_timeout(&mut self, timeout: Option<Duration>) {
+        self.escape_timeout = timeout;
+    }
+
+    /// Enables or disables keyboard macros, see the
+    /// [`macros`](../macros/index.html) module. Disabling macros discards all
+    /// stored macros.
+    pub fn set_macros_enabled(&mut self, enabled: bool) {
+        match (enabled, self.macros.is_some()) {
+            (true, false) => self.macros = Some(Macros::new()),
+            (false, true) => self.macros = None,
+            _ => {}
+        }
+    }
+
+    /// Returns the stored keyboard macros, or `None` if macros are disabled.
+    pub fn macros(&self) -> Option<&Macros> {
+        self.macros.as_ref()
+    }
+
+    /// Returns the stored keyboard macros for modification, or `None` if
+    /// macros are disabled.
+    pub fn macros_mut(&mut self) -> Option<&mut Macros> {
+        self.macros.as_mut()
+    }
+
+    /// Starts recording the keys delivered to components into a macro. A
+    /// recording in progress is discarded. Returns `false` if macros are
+    /// disabled.
+    pub fn start_macro(&mut self, name: impl Into<String>) -> bool {
+        match self.macros.as_mut() {
+            Some(macros) => {
+                macros.start(name.into());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops recording and stores the macro. Returns `false` if no macro was
+    /// being recorded.
+    pub fn stop_macro(&mut self) -> bool {
+        self.macros
+            .as_mut()
+            .map_or(false, |macros| macros.stop(false))
+    }
+
+    /// Queues the keys of a macro to be replayed `count` times. Returns
+    /// `false` if there is no macro with this name.
+    ///
+    /// The keys are dispatched by
+    /// [`handle_queued_input`](#method.handle_queued_input).
+    pub fn replay_macro(&mut self, name: &str, count: usize) -> bool {
+        self.macros
+            .as_mut()
+            .map_or(false, |macros| macros.queue(name, count))
+    }
+
+    /// Queues the keys of the most recently recorded macro to be replayed
+    /// `count` times. Returns `false` if no macro was recorded yet.
+    pub fn replay_last_macro(&mut self, count: usize) -> bool {
+        match self.macros.as_ref().and_then(Macros::last) {
+            Some(name) => {
+                let name = name.to_owned();
+                self.replay_macro(&name, count)
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if [`handle_queued_input`](#method.handle_queued_input)
+    /// should be called, i.e. while a macro is being replayed or pasted text is
+    /// being typed as keys.
+    pub fn has_queued_input(&self) -> bool {
+        !self.pasted_keys.is_empty() || self.macros.as_ref().map_or(false, Macros::is_replaying)
+    }
+
+    /// Dispatches the next queued key, returning `false` once there are none
+    /// left. The keys of pasted text come before those of a macro.
+    ///
+    /// Backends should call this repeatedly while
+    /// [`has_queued_input`](#method.has_queued_input) returns `true`, after
+    /// delivering pending messages and redrawing, so each key sees the
+    /// effects of the previous one.
+    pub fn handle_queued_input(&mut self) -> bool {
+        let key = match self.pasted_keys.pop_front() {
+            Some(key) => Some(key),
+            None => self.macros.as_mut().and_then(Macros::next_queued),
+        };
+        match key {
+            Some(key) => {
+                self.handle_key(key);
+                self.runtime.poll_state.merge(PollState::Dirty(None));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the earliest time pending input should be resolved by calling
+    /// [`handle_timeout`](#method.handle_timeout), if any.
+    ///
+    /// Input is pending after a key sequence prefix bound in a keymap with a
+    /// sequence timeout, see
+    /// [`Bindings::set_sequence_timeout`](../struct.Bindings.html#method.set_sequence_timeout),
+    /// or after a bare `Esc` with an [escape timeout](#method.set_escape_timeout).
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let sequence_deadline = self.pending_sequence.map(|pending| pending.deadline);
+        match (sequence_deadline, self.pending_escape) {
+            (Some(sequence), Some(escape)) => Some(std::cmp::min(sequence, escape)),
+            (sequence, escape) => sequence.or(escape),
+        }
+    }
+
+    /// Resolves pending input whose deadline is at or before `now`.
+    ///
+    /// A pending key sequence runs the command bound to the keys pressed so
+    /// far, if any, and is otherwise discarded. A pending `Esc` is delivered
+    /// on its own.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        if let Some(pending) = self.pending_sequence {
+            if pending.deadline <= now {
+                self.resolve_pending_sequence();
+                self.runtime.poll_state.merge(PollState::Dirty(None));
+            }
+        }
+        if let Some(deadline) = self.pending_escape {
+            if deadline <= now {
+                self.pending_escape = None;
+                self.handle_key(KeyCode::Esc.into());
+                self.runtime.poll_state.merge(PollState::Dirty(None));
+            }
+        }
+    }
+
     /// Return `true` if any components currently mounted are tickable
     #[inline]
     pub fn is_tickable(&mut self) -> bool {
@@ -182,7 +440,9 @@ impl App {
     ///
     /// This function flushes all pending changes to the component tree,
     /// computes the layout and redraws components where needed. After calling this
-    /// function `poll_state()` will be `PollState::Clean`
+    /// function `poll_state()` will be `PollState::Clean`, unless a component
+    /// gaining or losing focus as a result of the new layout needs to be
+    /// drawn again.
     #[inline]
     pub fn draw(&mut self) -> &Canvas {
         match self.runtime.poll_state {
@@ -202,6 +462,10 @@ impl App {
 
                 let frame = Rect::new(Position::new(0, 0), self.runtime.screen.size());
                 let statistics = self.draw_tree(frame, self.runtime.num_frame);
+                self.runtime.poll_state = PollState::Clean;
+
+                // Components gaining or losing focus may need to be drawn again
+                self.update_focus();
                 let drawn_time = now.elapsed();
 
                 // Present
@@ -223,9 +487,8 @@ impl App {
             PollState::Exit => {
                 panic!("tried drawing while the app is exiting");
             }
-            _ => {}
+            PollState::Clean => {}
         }
-        self.runtime.poll_state = PollState::Clean;
         &self.runtime.screen
     }
 
@@ -239,22 +502,64 @@ impl App {
     pub fn handle_message(&mut self, message: ComponentMessage) {
         match message.0 {
             LinkMessage::Component(component_id, dyn_message) => {
-                let should_render = self
-                    .components
-                    .get_mut(&component_id)
-                    .map(|component| component.update(dyn_message))
+                self.update_component(component_id, dyn_message);
+            }
+            LinkMessage::Task(output) => {
+                // Checked here rather than when the task finishes, as the
+                // component may be unmounted while the output is queued
+                if let Some((component_id, dyn_message)) = output.into_message() {
+                    self.update_component(component_id, dyn_message);
+                }
+            }
+            LinkMessage::Focus(request) => {
+                let focused = match request {
+                    FocusRequest::Request(component_id) => self
+                        .components
+                        .get(&component_id)
+                        .filter(|component| component.bindings.focusable())
+                        .map(|_| component_id)
+                        .or(self.focus.focused),
+                    FocusRequest::Release(component_id)
+                        if self.focus.focused == Some(component_id) =>
+                    {
+                        self.focus.released = true;
+                        None
+                    }
+                    FocusRequest::Release(_) => self.focus.focused,
+                    FocusRequest::Next => self.focus.next(),
+                    FocusRequest::Previous => self.focus.previous(),
+                };
+                self.set_focus(focused);
+            }
+            LinkMessage::Commands(component_id, reply) => {
+                let commands = self.commands_outside(Some(component_id));
+                self.update_component(component_id, reply(commands));
+            }
+            LinkMessage::RunCommand(excluded, name) => {
+                match self.run_command_outside(&name, excluded) {
+                    CommandOutcome::Ran => {}
+                    CommandOutcome::Declined => log::warn!(
+                        "Command `{}` declined to run without the keys bound to it",
+                        name
+                    ),
+                    CommandOutcome::Missing => {
+                        log::debug!("No reachable component has a command named `{}`", name)
+                    }
+                }
+            }
+            LinkMessage::Macro(request) => self.handle_macro_request(request),
+            LinkMessage::SetTheme(theme) => self.set_theme(theme),
+            LinkMessage::Dispatch(action) => {
+                let messages = self
+                    .store
+                    .write(|store| store.dispatch(action))
                     .unwrap_or_else(|| {
-                        log::debug!(
-                            "Received message for nonexistent component (id: {}).",
-                            component_id,
-                        );
-                        false
+                        log::debug!("Ignoring an action dispatched without a store");
+                        Vec::new()
                     });
-                self.runtime.poll_state.merge(if should_render {
-                    PollState::Dirty(None)
-                } else {
-                    PollState::Clean
-                });
+                for (component_id, message) in messages {
+                    self.update_component(component_id, message);
+                }
             }
             LinkMessage::Exit => {
                 self.runtime.poll_state.merge(PollState::Exit);
@@ -262,52 +567,477 @@ impl App {
         }
     }
 
+    fn handle_macro_request(&mut self, request: MacroRequest) {
+        let macros = match self.macros.as_mut() {
+            Some(macros) => macros,
+            None => {
+                log::debug!("Ignoring {:?}, keyboard macros are disabled", request);
+                return;
+            }
+        };
+        match request {
+            MacroRequest::Start(name) => macros.start(name),
+            // Requested by a key binding, which isn't part of the macro
+            MacroRequest::Stop => {
+                macros.stop(true);
+            }
+            MacroRequest::Replay(..) if macros.is_replaying() => {
+                log::warn!("Ignoring a macro replay requested while replaying a macro");
+            }
+            MacroRequest::Replay(Some(name), count) => {
+                if !self.replay_macro(&name, count) {
+                    log::debug!("No macro named `{}`", name);
+                }
+            }
+            MacroRequest::Replay(None, count) => {
+                self.replay_last_macro(count);
+            }
+        }
+    }
+
+    /// Returns the commands of all mounted components.
+    ///
+    /// Commands reachable from the focused components, i.e. those that a key
+    /// press could trigger, come first, innermost component first. The rest
+    /// follow ordered by their depth in the layout tree.
+    pub fn commands(&self) -> Vec<CommandInfo> {
+        self.commands_outside(None)
+    }
+
+    /// Runs a command by name, as if one of its key bindings was pressed.
+    ///
+    /// The command is looked up on the focused components and their ancestors,
+    /// innermost first, i.e. the components a key press would be offered to.
+    /// Like for key presses, if the command doesn't handle the invocation it
+    /// is offered to the next component. Returns `true` if a component ran a
+    /// command with the given name.
+    pub fn run_command(&mut self, name: &str) -> bool {
+        self.run_command_outside(name, None) == CommandOutcome::Ran
+    }
+
+    #[inline]
+    fn update_component(&mut self, component_id: ComponentId, message: DynamicMessage) {
+        let should_render = self
+            .components
+            .get_mut(&component_id)
+            .map(|component| component.update(message))
+            .unwrap_or_else(|| {
+                log::debug!(
+                    "Received message for nonexistent component (id: {}).",
+                    component_id,
+                );
+                false
+            });
+        self.runtime.poll_state.merge(if should_render {
+            PollState::Dirty(None)
+        } else {
+            PollState::Clean
+        });
+    }
+
+    /// Like `commands`, but leaves out the commands of `excluded` and its
+    /// descendants.
+    fn commands_outside(&self, excluded: Option<ComponentId>) -> Vec<CommandInfo> {
+        let dispatch_order = self.dispatch_order(excluded);
+        let mut other_components: Vec<_> = self
+            .components
+            .iter()
+            .filter(|(component_id, _)| {
+                !dispatch_order.contains(component_id) && !self.is_within(**component_id, excluded)
+            })
+            .map(|(component_id, component)| (*component_id, component))
+            .collect();
+        other_components.sort_by_key(|(component_id, component)| {
+            (
+                component.depth,
+                component.frame.origin.y,
+                component.frame.origin.x,
+                component_id.type_name(),
+            )
+        });
+
+        let reachable = dispatch_order.iter().map(|component_id| {
+            let component = self
+                .components
+                .get(component_id)
+                .expect("focused component to be mounted");
+            (*component_id, component, true)
+        });
+        let other = other_components
+            .into_iter()
+            .map(|(component_id, component)| (component_id, component, false));
+
+        let mut commands = Vec::new();
+        for (component_id, component, reachable) in reachable.chain(other) {
+            let keymap = component.bindings.keymap();
+            commands.extend(keymap.commands().map(|(command_id, name)| CommandInfo {
+                name: name.to_owned().into(),
+                component: component_id.type_name(),
+                keys: keymap.command_keys(command_id),
+                reachable,
+            }));
+        }
+        commands
+    }
+
+    /// Like `run_command`, but skips the commands of `excluded` and its
+    /// descendants.
+    fn run_command_outside(&mut self, name: &str, excluded: Option<ComponentId>) -> CommandOutcome {
+        let mut outcome = CommandOutcome::Missing;
+        for component_id in self.dispatch_order(excluded) {
+            let component = self
+                .components
+                .get_mut(&component_id)
+                .expect("focused component to be mounted");
+            let command_id = match component.bindings.keymap().command_id(name) {
+                Some(command_id) => command_id,
+                None => continue,
+            };
+            let message =
+                match component
+                    .renderable
+                    .run_command(&component.bindings, command_id, &[])
+                {
+                    Some(message) => message,
+                    None if component
+                        .bindings
+                        .command_bubbles_when_unhandled(command_id) =>
+                    {
+                        outcome = CommandOutcome::Declined;
+                        continue;
+                    }
+                    None => return CommandOutcome::Ran,
+                };
+            if component.update(message) {
+                self.runtime.poll_state.merge(PollState::Dirty(None));
+            }
+            return CommandOutcome::Ran;
+        }
+        outcome
+    }
+
+    /// Focused components and their ancestors, innermost first, except for
+    /// `excluded` and its descendants. Components at the same depth keep their
+    /// layout order. Only components focused in the topmost overlay are
+    /// included.
+    fn dispatch_order(&self, excluded: Option<ComponentId>) -> SmallVec<[ComponentId; 8]> {
+        let mut dispatch_order = SmallVec::<[ComponentId; 8]>::new();
+        let focused = self
+            .subscriptions
+            .focused
+            .iter()
+            .copied()
+            .chain(self.focus.focused);
+        let top_level = self.top_level(focused.clone());
+        for focused_id in focused.filter(|component_id| self.level(*component_id) == top_level) {
+            let mut component_id = Some(focused_id);
+            while let Some(current_id) = component_id {
+                if dispatch_order.contains(&current_id) {
+                    break;
+                }
+                if !self.is_within(current_id, excluded) {
+                    dispatch_order.push(current_id);
+                }
+                component_id = self
+                    .components
+                    .get(&current_id)
+                    .and_then(|component| component.parent);
+            }
+        }
+        dispatch_order.sort_by_key(|component_id| {
+            std::cmp::Reverse(
+                self.components
+                    .get(component_id)
+                    .map(|component| component.depth)
+                    .unwrap_or_default(),
+            )
+        });
+        dispatch_order
+    }
+
+    /// Returns the overlay level of a mounted component.
+    #[inline]
+    fn level(&self, component_id: ComponentId) -> usize {
+        self.components
+            .get(&component_id)
+            .map(|component| component.level)
+            .unwrap_or_default()
+    }
+
+    /// Returns the highest overlay level of the given components.
+    #[inline]
+    fn top_level(&self, component_ids: impl Iterator<Item = ComponentId>) -> usize {
+        component_ids
+            .map(|component_id| self.level(component_id))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if `component_id` is `ancestor_id` or one of its
+    /// descendants.
+    fn is_within(&self, component_id: ComponentId, ancestor_id: Option<ComponentId>) -> bool {
+        let ancestor_id = match ancestor_id {
+            Some(ancestor_id) => ancestor_id,
+            None => return false,
+        };
+        let mut component_id = Some(component_id);
+        while let Some(current_id) = component_id {
+            if current_id == ancestor_id {
+                return true;
+            }
+            component_id = self
+                .components
+                .get(&current_id)
+                .and_then(|component| component.parent);
+        }
+        false
+    }
+
     #[inline]
     pub fn handle_input(&mut self, event: Event) {
         match event {
             Event::Key(key_event) => {
-                self.handle_key(key_event);
+                self.handle_key_input(key_event);
                 self.runtime.poll_state.merge(PollState::Dirty(None));
             }
+            Event::Mouse(mouse_event) => {
+                self.flush_pending_escape();
+                self.handle_mouse(mouse_event);
+                self.runtime.poll_state.merge(PollState::Dirty(None));
+            }
+            Event::Paste(text) => {
+                self.flush_pending_escape();
+                self.handle_paste(&text);
+                self.runtime.poll_state.merge(PollState::Dirty(None));
+            }
+        }
+    }
+
+    /// Delivers pasted text to the focused components and their ancestors,
+    /// innermost first, like keys, see `handle_key`. The first component with
+    /// a paste handler that accepts the text consumes it.
+    ///
+    /// If no component accepts the text, it is queued as a sequence of key
+    /// events instead, which are dispatched one at a time by
+    /// [`handle_queued_input`](#method.handle_queued_input) like a macro.
+    #[inline]
+    fn handle_paste(&mut self, text: &str) {
+        for component_id in self.dispatch_order(None) {
+            let component = self
+                .components
+                .get_mut(&component_id)
+                .expect("focused component to be mounted");
+            if let Some(message) = component
+                .renderable
+                .run_paste_handler(&component.bindings, text)
+            {
+                component.update(message);
+                return;
+            }
+        }
+
+        let mut characters = text.chars().peekable();
+        while let Some(character) = characters.next() {
+            let code = match character {
+                '\r' => {
+                    characters.next_if_eq(&'\n');
+                    KeyCode::Enter
+                }
+                '\n' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                character => KeyCode::Char(character),
+            };
+            self.pasted_keys.push_back(code.into());
         }
     }
 
+    /// Delivers a mouse event to the topmost component under the cursor.
+    ///
+    /// Components are hit-tested in reverse draw order, so descendants are
+    /// offered the event before their ancestors. Only components in the
+    /// topmost overlay under the cursor are considered. The first mouse handler
+    /// that returns a message stops the search.
+    #[inline]
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        let Self {
+            ref mut components,
+            ref subscriptions,
+            ..
+        } = *self;
+
+        // Overlays hide whatever is under them from the mouse
+        let top_level = components
+            .values()
+            .filter(|component| component.contains(event.position))
+            .map(|component| component.level)
+            .max()
+            .unwrap_or_default();
+        for component_id in subscriptions.mouse.iter().rev() {
+            let component = components
+                .get_mut(component_id)
+                .expect("mouse subscribed component to be mounted");
+            if !component.contains(event.position) || component.level != top_level {
+                continue;
+            }
+
+            let position = component.clip.screen_to_layout(event.position);
+            let local_event = MouseEvent {
+                position: Position::new(
+                    position.x - component.frame.origin.x,
+                    position.y - component.frame.origin.y,
+                ),
+                ..event
+            };
+            if let Some(message) = component
+                .renderable
+                .run_mouse_handler(&component.bindings, local_event)
+            {
+                component.update(message);
+                break;
+            }
+        }
+    }
+
+    /// Delivers a key press, combining it with a pending `Esc` into an `Alt`
+    /// chord if it arrived before the escape timeout.
+    fn handle_key_input(&mut self, key: KeyEvent) {
+        if let Some(deadline) = self.pending_escape.take() {
+            if Instant::now() < deadline
+                && key.code != KeyCode::Esc
+                && !key.modifiers.contains(KeyModifiers::ALT)
+            {
+                self.handle_key(KeyEvent::new(key.code, key.modifiers | KeyModifiers::ALT));
+                return;
+            }
+            self.handle_key(KeyCode::Esc.into());
+        }
+
+        match self.escape_timeout {
+            Some(timeout) if key.code == KeyCode::Esc && key.modifiers.is_empty() => {
+                self.pending_escape = Some(Instant::now() + timeout);
+            }
+            _ => self.handle_key(key),
+        }
+    }
+
+    /// Delivers a pending `Esc` on its own, before input which can't be
+    /// combined with it into an `Alt` chord.
+    fn flush_pending_escape(&mut self) {
+        if self.pending_escape.take().is_some() {
+            self.handle_key(KeyCode::Esc.into());
+        }
+    }
+
+    /// Runs the command bound to a pending key sequence after its timeout
+    /// expired, if there is one, and clears the sequence.
+    fn resolve_pending_sequence(&mut self) {
+        let PendingSequence { component_id, .. } = match self.pending_sequence.take() {
+            Some(pending) => pending,
+            None => return,
+        };
+        let keys = std::mem::take(&mut self.controller.keys);
+        let mut binding_queries = SmallVec::<[_; 1]>::new();
+        if let Some(component) = self.components.get_mut(&component_id) {
+            let keymap = component.bindings.keymap();
+            if let Some(command_id) = keymap.check_sequence_timeout(&keys) {
+                binding_queries.push(Some(NamedBindingQuery::Match(
+                    keymap.name(&command_id).to_owned().into(),
+                )));
+                if let Some(message) =
+                    component
+                        .renderable
+                        .run_command(&component.bindings, command_id, &keys)
+                {
+                    component.update(message);
+                }
+            }
+        }
+
+        for component_id in self.subscriptions.notify.iter() {
+            let notify_component = self
+                .components
+                .get_mut(component_id)
+                .expect("component to be mounted");
+            notify_component
+                .renderable
+                .notify_binding_queries(&binding_queries, &keys);
+        }
+    }
+
+    /// Offers a key to the focused components, innermost first.
+    ///
+    /// The key (together with any pending prefix) goes to the innermost focused
+    /// component and then bubbles up through its ancestors in the layout tree.
+    /// It stops at the first component that consumes it, i.e. where the keys
+    /// are a prefix of a binding or match a command that handles them.
     #[inline]
     fn handle_key(&mut self, key: KeyEvent) {
+        // A key that doesn't continue a sequence waiting for its timeout
+        // resolves the sequence early and is then handled on its own
+        if let Some(PendingSequence { component_id, .. }) = self.pending_sequence {
+            let mut keys = self.controller.keys.clone();
+            keys.push(key);
+            let continues_sequence = self
+                .components
+                .get(&component_id)
+                .map_or(false, |component| {
+                    component.bindings.keymap().check_sequence(&keys).is_some()
+                });
+            if !continues_sequence {
+                self.resolve_pending_sequence();
+            }
+        }
+
+        if let Some(macros) = self.macros.as_mut() {
+            macros.record(key, self.controller.keys.is_empty());
+        }
+
+        let dispatch_order = self.dispatch_order(None);
         let Self {
             ref mut components,
             ref subscriptions,
             controller: ref mut input_controller,
+            ref mut pending_sequence,
             ..
         } = *self;
         let mut clear_controller = true;
-        let mut binding_queries = SmallVec::<[_; 4]>::with_capacity(subscriptions.focused.len());
+        *pending_sequence = None;
+        let mut consumed = false;
+        let mut binding_queries = SmallVec::<[_; 4]>::with_capacity(dispatch_order.len());
 
         input_controller.push(key);
-        for component_id in subscriptions.focused.iter() {
-            let focused_component = components
+        for component_id in dispatch_order.iter() {
+            let component = components
                 .get_mut(component_id)
                 .expect("focused component to be mounted");
 
-            let binding_query = focused_component
+            let binding_query = component
                 .bindings
                 .keymap()
                 .check_sequence(&input_controller.keys);
             binding_queries.push(binding_query.map(|binding_query| {
-                NamedBindingQuery::new(focused_component.bindings.keymap(), binding_query)
+                NamedBindingQuery::new(
+                    component.bindings.keymap(),
+                    binding_query,
+                    &input_controller.keys,
+                )
             }));
-            match focused_component
-                .bindings
-                .keymap()
-                .check_sequence(&input_controller.keys)
-            {
+            match binding_query {
                 Some(BindingQuery::Match(command_id)) => {
-                    if let Some(message) = focused_component.renderable.run_command(
-                        &focused_component.bindings,
+                    match component.renderable.run_command(
+                        &component.bindings,
                         *command_id,
                         &input_controller.keys,
                     ) {
-                        focused_component.update(message);
+                        Some(message) => {
+                            component.update(message);
+                            consumed = true;
+                        }
+                        None => {
+                            consumed = !component
+                                .bindings
+                                .command_bubbles_when_unhandled(*command_id);
+                        }
                     }
                 }
                 Some(BindingQuery::PrefixOf(prefix_of)) => {
@@ -316,10 +1046,24 @@ impl App {
                         KeySequenceSlice::from(input_controller.keys.as_slice()),
                         prefix_of.len()
                     );
+                    consumed = true;
                     clear_controller = false;
+                    *pending_sequence =
+                        component
+                            .bindings
+                            .keymap()
+                            .sequence_timeout()
+                            .map(|timeout| PendingSequence {
+                                component_id: *component_id,
+                                deadline: Instant::now() + timeout,
+                            });
                 }
                 None => {}
             }
+
+            if consumed {
+                break;
+            }
         }
 
         for component_id in subscriptions.notify.iter() {
@@ -335,6 +1079,62 @@ impl App {
         if clear_controller {
             input_controller.keys.clear();
         }
+
+        // Unless a focused component binds them, `Tab` and `BackTab` move
+        // focus along the focus chain
+        if !consumed {
+            match key.code {
+                KeyCode::Tab => self.set_focus(self.focus.next()),
+                KeyCode::BackTab => self.set_focus(self.focus.previous()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves runtime managed focus to a new component (or none), notifying the
+    /// components that lost and gained focus.
+    fn set_focus(&mut self, focused: Option<ComponentId>) {
+        if self.focus.focused == focused {
+            return;
+        }
+        if focused.is_some() {
+            self.focus.released = false;
+        }
+
+        let mut needs_render = false;
+        if let Some(component) = self
+            .focus
+            .focused
+            .and_then(|component_id| self.components.get_mut(&component_id))
+        {
+            needs_render |= component.focus_lost();
+        }
+        self.focus.focused = focused;
+        if let Some(component) =
+            focused.and_then(|component_id| self.components.get_mut(&component_id))
+        {
+            needs_render |= component.focus_gained();
+        }
+        if needs_render {
+            self.runtime.poll_state.merge(PollState::Dirty(None));
+        }
+    }
+
+    /// Reconciles runtime managed focus with the focus chain from the latest
+    /// draw. If the focused component is gone or isn't focusable anymore,
+    /// focus moves to the first focusable component, unless focus was
+    /// explicitly released.
+    fn update_focus(&mut self) {
+        let focused = self
+            .focus
+            .focused
+            .filter(|component_id| self.focus.chain.contains(component_id));
+        if focused != self.focus.focused {
+            self.set_focus(focused);
+        }
+        if self.focus.focused.is_none() && !self.focus.released {
+            self.set_focus(self.focus.chain.first().copied());
+        }
     }
 
     #[inline]
@@ -344,20 +1144,62 @@ impl App {
             ref mut layouts,
             ref mut runtime,
             ref mut subscriptions,
+            ref mut focus,
+            ref keymap_overrides,
+            ref theme,
+            ref store,
+            ref spawner,
             ref sender,
             ..
         } = *self;
 
         subscriptions.clear();
+        focus.chain.clear();
 
         let mut first = true;
         let mut pending = Vec::new();
+        let mut children = Vec::new();
+        // Overlays are drawn after everything on lower levels, keyed by level
+        let mut deferred = BTreeMap::<usize, Vec<_>>::new();
+        let mut deferred_canvases = BTreeMap::<usize, Vec<(Rect, Clip, Canvas)>>::new();
         let mut statistics = DrawStatistics::default();
         loop {
-            let (layout, frame2, position_hash, parent_changed) = if first {
+            if pending.is_empty() && !first {
+                let level = match deferred.keys().next() {
+                    Some(&level) => level,
+                    None => break,
+                };
+                for (frame, clip, canvas) in deferred_canvases.remove(&level).unwrap_or_default() {
+                    draw_clipped(&mut runtime.screen, &canvas, frame, clip);
+                }
+                let components = deferred.remove(&level).expect("level to exist");
+                pending.extend(components.into_iter().rev());
+            }
+
+            let (
+                layout,
+                frame2,
+                position_hash,
+                parent_changed,
+                parent,
+                depth,
+                level,
+                clip2,
+                child_contexts,
+            ) = if first {
                 first = false;
-                (&mut self.root, frame, 0, false)
-            } else if let Some((component_id, frame, position_hash)) = pending.pop() {
+                (
+                    &mut self.root,
+                    frame,
+                    0,
+                    false,
+                    None,
+                    0,
+                    0,
+                    Clip::new(frame),
+                    Arc::new(ContextMap::default()),
+                )
+            } else if let Some((component_id, frame, position_hash, clip)) = pending.pop() {
                 let component = components
                     .get_mut(&component_id)
                     .expect("Layout is cached only for mounted components");
@@ -369,7 +1211,21 @@ impl App {
                     *layout = component.view()
                 }
                 component.set_generation(generation);
-                (layout, frame, position_hash, changed)
+                if component.bindings.focusable() {
+                    focus.chain.push(component_id);
+                }
+                let depth = component.depth + 1;
+                (
+                    layout,
+                    frame,
+                    position_hash,
+                    changed,
+                    Some(component_id),
+                    depth,
+                    component.level,
+                    clip,
+                    component.contexts.for_children(),
+                )
             } else {
                 break;
             };
@@ -377,25 +1233,49 @@ impl App {
             layout.0.crawl(
                 frame2,
                 position_hash,
+                level,
+                clip2,
                 &mut |LaidComponent {
                           frame,
                           position_hash,
+                          level: component_level,
+                          clip,
                           template,
                       }| {
                     let component_id = template.generate_id(position_hash);
                     let mut new_component = false;
                     let component = components.entry(component_id).or_insert_with(|| {
                         new_component = true;
-                        let (renderable, bindings) =
-                            template.create(component_id, frame, sender.clone_box());
+                        let runtime = ComponentRuntime {
+                            theme: theme.clone(),
+                            contexts: SharedContexts::new(child_contexts.clone()),
+                            store: store.clone(),
+                            tasks: ComponentTasks::new(spawner.clone()),
+                        };
+                        let (renderable, bindings) = template.create(
+                            component_id,
+                            frame,
+                            sender.clone_box(),
+                            runtime.clone(),
+                        );
                         MountedComponent {
                             renderable,
                             frame,
                             bindings,
                             should_render: ShouldRender::Yes.into(),
                             generation,
+                            parent,
+                            depth,
+                            level: component_level,
+                            clip,
+                            contexts: runtime.contexts,
+                            tasks: runtime.tasks,
                         }
                     });
+                    component.parent = parent;
+                    component.depth = depth;
+                    component.level = component_level;
+                    component.clip = clip;
 
                     if !new_component {
                         let mut changed =
@@ -403,6 +1283,12 @@ impl App {
                         if frame != component.frame {
                             changed = component.resize(frame) || changed;
                         }
+                        // Descendants are re-rendered when a provided value
+                        // changes
+                        if component.contexts.set_inherited(child_contexts.clone()) {
+                            component.should_render = true;
+                            changed = true;
+                        }
                         if changed {
                             statistics.changed += 1;
                         } else {
@@ -412,7 +1298,7 @@ impl App {
                         statistics.new += 1;
                     }
 
-                    component.update_bindings();
+                    component.update_bindings(component_id, keymap_overrides.as_ref());
                     if component.bindings.focused() {
                         subscriptions.add_focused(component_id);
                     }
@@ -421,35 +1307,74 @@ impl App {
                         subscriptions.add_notify(component_id);
                     }
 
+                    if component.bindings.has_mouse_handler() {
+                        subscriptions.add_mouse(component_id);
+                    }
+
                     if let Some(message) = component.tick() {
                         subscriptions.add_tickable(component_id, message);
                     }
 
-                    pending.push((component_id, frame, position_hash));
+                    children.push((component_id, frame, position_hash, clip, component_level));
                 },
-                &mut |LaidCanvas { frame, canvas, .. }| {
-                    runtime.screen.copy_region(canvas, frame);
+                &mut |LaidCanvas {
+                          frame,
+                          level: canvas_level,
+                          clip,
+                          canvas,
+                      }| {
+                    if canvas_level > level {
+                        deferred_canvases.entry(canvas_level).or_default().push((
+                            frame,
+                            clip,
+                            canvas.clone(),
+                        ));
+                    } else {
+                        draw_clipped(&mut runtime.screen, canvas, frame, clip);
+                    }
                 },
             );
+
+            // Visit children in layout order, depth first, overlays last. This
+            // is the order of the focus chain.
+            for &(component_id, frame, position_hash, clip, component_level) in children.iter() {
+                if component_level > level {
+                    deferred.entry(component_level).or_default().push((
+                        component_id,
+                        frame,
+                        position_hash,
+                        clip,
+                    ));
+                }
+            }
+            pending.extend(
+                children
+                    .drain(..)
+                    .rev()
+                    .filter(|&(_, _, _, _, component_level)| component_level <= level)
+                    .map(|(component_id, frame, position_hash, clip, _)| {
+                        (component_id, frame, position_hash, clip)
+                    }),
+            );
         }
 
         // Drop components that are not part of the current layout tree, i.e. do
         // not appear on the screen.
-        components.retain(
-            |component_id,
-             &mut MountedComponent {
-                 generation: component_generation,
-                 ..
-             }| {
-                if component_generation < generation {
-                    statistics.deleted += 1;
-                    layouts.remove(component_id);
-                    false
-                } else {
-                    true
-                }
-            },
-        );
+        let mut unmounted = Vec::new();
+        components.retain(|component_id, component| {
+            if component.generation < generation {
+                statistics.deleted += 1;
+                layouts.remove(component_id);
+                component.tasks.cancel();
+                unmounted.push(*component_id);
+                false
+            } else {
+                true
+            }
+        });
+        if !unmounted.is_empty() {
+            store.write(|store| store.unsubscribe(&unmounted));
+        }
 
         statistics
     }
@@ -458,6 +1383,7 @@ impl App {
 struct ComponentSubscriptions {
     focused: SmallVec<[ComponentId; 2]>,
     notify: SmallVec<[ComponentId; 2]>,
+    mouse: SmallVec<[ComponentId; 2]>,
     tickable: SmallVec<[TickSubscription; 2]>,
 }
 
@@ -466,6 +1392,7 @@ impl ComponentSubscriptions {
         Self {
             focused: SmallVec::new(),
             notify: SmallVec::new(),
+            mouse: SmallVec::new(),
             tickable: SmallVec::new(),
         }
     }
@@ -474,6 +1401,7 @@ impl ComponentSubscriptions {
     fn clear(&mut self) {
         self.focused.clear();
         self.notify.clear();
+        self.mouse.clear();
         self.tickable.clear();
     }
 
@@ -487,6 +1415,11 @@ impl ComponentSubscriptions {
         self.notify.push(component_id);
     }
 
+    #[inline]
+    fn add_mouse(&mut self, component_id: ComponentId) {
+        self.mouse.push(component_id);
+    }
+
     #[inline]
     fn add_tickable(&mut self, component_id: ComponentId, message: DynamicMessage) {
         self.tickable.push(TickSubscription {
@@ -501,6 +1434,54 @@ struct TickSubscription {
     message: DynamicMessage,
 }
 
+/// Focus managed by the runtime.
+struct FocusChain {
+    /// The focused component, if any.
+    focused: Option<ComponentId>,
+    /// Focusable components in layout order, as of the latest draw.
+    chain: Vec<ComponentId>,
+    /// Whether focus was explicitly released. The first focusable component
+    /// is focused automatically otherwise.
+    released: bool,
+}
+
+impl FocusChain {
+    fn new() -> Self {
+        Self {
+            focused: None,
+            chain: Vec::new(),
+            released: false,
+        }
+    }
+
+    /// Returns the focusable component after the focused one, wrapping around.
+    fn next(&self) -> Option<ComponentId> {
+        let index = match self.focused_index() {
+            Some(index) => (index + 1) % self.chain.len(),
+            None => 0,
+        };
+        self.chain.get(index).copied()
+    }
+
+    /// Returns the focusable component before the focused one, wrapping around.
+    fn previous(&self) -> Option<ComponentId> {
+        let index = match self.focused_index() {
+            Some(index) => index.checked_sub(1).unwrap_or(self.chain.len() - 1),
+            None => self.chain.len().checked_sub(1)?,
+        };
+        self.chain.get(index).copied()
+    }
+
+    #[inline]
+    fn focused_index(&self) -> Option<usize> {
+        self.focused.and_then(|focused| {
+            self.chain
+                .iter()
+                .position(|component_id| *component_id == focused)
+        })
+    }
+}
+
 impl PollState {
     pub fn dirty(&self) -> bool {
         matches!(*self, Self::Dirty(_))
@@ -533,6 +1514,22 @@ struct MountedComponent {
     bindings: DynamicBindings,
     generation: Generation,
     should_render: bool,
+    /// The closest ancestor component in the layout tree, `None` for
+    /// components in the root layout.
+    parent: Option<ComponentId>,
+    /// The number of ancestor components.
+    depth: usize,
+    /// The overlay level, i.e. the number of overlays the component is nested
+    /// in, see [`Stack`](../layout/struct.Stack.html).
+    level: usize,
+    /// The visible region of the layout space the component's frame is in,
+    /// which is smaller than the screen inside a viewport.
+    clip: Clip,
+    /// The values provided to the component by its ancestors and by the
+    /// component to its descendants.
+    contexts: SharedContexts,
+    /// The tasks spawned by the component, cancelled when it is unmounted.
+    tasks: ComponentTasks,
 }
 
 impl MountedComponent {
@@ -558,12 +1555,34 @@ impl MountedComponent {
     #[inline]
     fn view(&mut self) -> Layout {
         self.should_render = false;
+        self.contexts.clear_provided();
         self.renderable.view()
     }
 
     #[inline]
-    fn update_bindings(&mut self) {
-        self.renderable.bindings(&mut self.bindings)
+    fn update_bindings(&mut self, component_id: ComponentId, overrides: Option<&KeymapOverrides>) {
+        let was_empty = self.bindings.keymap().is_empty();
+        self.renderable.bindings(&mut self.bindings);
+
+        // Overrides are applied once, right after a component sets up its
+        // default key bindings
+        if let Some(overrides) = overrides {
+            if was_empty && !self.bindings.keymap().is_empty() {
+                overrides.apply(component_id.type_name(), self.bindings.keymap_mut());
+            }
+        }
+    }
+
+    #[inline]
+    fn focus_gained(&mut self) -> bool {
+        self.should_render = self.renderable.focus_gained().into() || self.should_render;
+        self.should_render
+    }
+
+    #[inline]
+    fn focus_lost(&mut self) -> bool {
+        self.should_render = self.renderable.focus_lost().into() || self.should_render;
+        self.should_render
     }
 
     #[inline]
@@ -575,6 +1594,71 @@ impl MountedComponent {
     fn set_generation(&mut self, generation: Generation) {
         self.generation = generation;
     }
+
+    /// Returns `true` if a position on the screen is in the visible part of
+    /// the component's frame.
+    #[inline]
+    fn contains(&self, position: Position) -> bool {
+        self.clip.visible(self.frame).contains(position)
+    }
+}
+
+/// Draws the visible part of a canvas laid out at `frame` to the screen.
+#[inline]
+fn draw_clipped(screen: &mut Canvas, canvas: &Canvas, frame: Rect, clip: Clip) {
+    let visible = clip.visible(frame);
+    let origin = clip.screen_to_layout(visible.origin);
+    let region = Rect::new(
+        Position::new(
+            origin.x.saturating_sub(frame.origin.x),
+            origin.y.saturating_sub(frame.origin.y),
+        ),
+        visible.size,
+    );
+    screen.copy_from(canvas, region, visible.origin);
+}
+
+/// A command of a mounted component, see [`App::commands`](struct.App.html#method.commands).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandInfo {
+    /// The name of the command.
+    pub name: Cow<'static, str>,
+    /// The type name of the component that owns the command.
+    pub component: &'static str,
+    /// The key patterns currently bound to the command.
+    pub keys: Vec<KeyPattern>,
+    /// Whether the command is reachable from the focused components, i.e.
+    /// whether a key press or `run_command` can trigger it.
+    pub reachable: bool,
+}
+
+impl CommandInfo {
+    /// Whether the command can be run by name, see
+    /// [`App::run_command`](struct.App.html#method.run_command). Commands bound
+    /// only to `AnyCharacter` can't, as they need the key that was typed.
+    pub fn runnable_by_name(&self) -> bool {
+        self.keys.is_empty()
+            || self
+                .keys
+                .iter()
+                .any(|pattern| !matches!(pattern, KeyPattern::AnyCharacter))
+    }
+}
+
+/// Whether running a command by name found a command which ran.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CommandOutcome {
+    Ran,
+    /// The commands with the name declined to handle the invocation.
+    Declined,
+    Missing,
+}
+
+/// A key sequence prefix waiting for its next key until a deadline.
+#[derive(Clone, Copy, Debug)]
+struct PendingSequence {
+    component_id: ComponentId,
+    deadline: Instant,
 }
 
 struct InputController {
@@ -595,31 +1679,7 @@ impl InputController {
 
 impl std::fmt::Display for InputController {
     fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        for key in self.keys.iter() {
-            let modifiers = key.modifiers;
-            let key_code = key.code;
-
-            match key_code {
-                KeyCode::Char(' ') => write!(formatter, "SPC ")?,
-                KeyCode::Enter => write!(formatter, "RET ")?,
-                KeyCode::Tab => write!(formatter, "TAB ")?,
-                KeyCode::Char(char) => {
-                    if modifiers.contains(KeyModifiers::CONTROL) {
-                        write!(formatter, "C-")?;
-                    }
-
-                    if modifiers.contains(KeyModifiers::ALT) {
-                        write!(formatter, "A-")?;
-                    }
-
-                    write!(formatter, "{} ", char)?
-                }
-                KeyCode::F(number) => write!(formatter, "F{} ", number)?,
-                KeyCode::Esc => write!(formatter, "ESC ")?,
-                key => write!(formatter, "{:?} ", key)?,
-            }
-        }
-        Ok(())
+        std::fmt::Display::fmt(&KeySequenceSlice::from(self.keys.as_slice()), formatter)
     }
 }
 
@@ -643,12 +1703,14 @@ impl std::fmt::Display for DrawStatistics {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::mpsc;
+    use std::{cell::RefCell, rc::Rc, sync::mpsc};
 
     use super::*;
     use crate::{
-        component::ComponentExt,
+        component::{Callback, ComponentExt},
         components::text::{Text, TextProperties},
+        prelude::*,
+        testing::TestBackend,
     };
 
     #[derive(Clone, Debug)]
@@ -700,6 +1762,660 @@ mod tests {
         }
     }
 
+    struct Clickable {
+        properties: ClickableProperties,
+    }
+
+    struct ClickableProperties {
+        id: usize,
+        clicks: Rc<RefCell<Vec<(usize, Position)>>>,
+    }
+
+    impl Component for Clickable {
+        type Message = Position;
+        type Properties = ClickableProperties;
+
+        fn create(properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self { properties }
+        }
+
+        fn update(&mut self, position: Self::Message) -> ShouldRender {
+            self.properties
+                .clicks
+                .borrow_mut()
+                .push((self.properties.id, position));
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.mouse(|event: MouseEvent| match event.kind {
+                MouseEventKind::Down(MouseButton::Left) => Some(event.position),
+                _ => None,
+            });
+        }
+    }
+
+    #[test]
+    fn mouse_events_are_routed_by_hit_testing() {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let mut app = TestBackend::new(
+            Size::new(10, 4),
+            Layout::row((0..2).map(|id| {
+                Item::auto(Clickable::with_key(
+                    id,
+                    ClickableProperties {
+                        id,
+                        clicks: clicks.clone(),
+                    },
+                ))
+            })),
+        );
+
+        let click = |x, y| {
+            Event::Mouse(MouseEvent::new(
+                MouseEventKind::Down(MouseButton::Left),
+                Position::new(x, y),
+                KeyModifiers::empty(),
+            ))
+        };
+        app.input(click(1, 1)).input(click(7, 3));
+        assert_eq!(
+            *clicks.borrow(),
+            vec![(0, Position::new(1, 1)), (1, Position::new(2, 3))]
+        );
+    }
+
+    struct Field {
+        properties: FieldProperties,
+        link: ComponentLink<Self>,
+    }
+
+    struct FieldProperties {
+        id: usize,
+        events: Rc<RefCell<Vec<(usize, bool)>>>,
+    }
+
+    impl Component for Field {
+        type Message = ();
+        type Properties = FieldProperties;
+
+        fn create(properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self { properties, link }
+        }
+
+        fn update(&mut self, _message: Self::Message) -> ShouldRender {
+            self.link.release_focus();
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focusable(true);
+            if bindings.is_empty() {
+                bindings.add("release-focus", [KeyEvent::from(KeyCode::Esc)], || ());
+            }
+        }
+
+        fn focus_gained(&mut self) -> ShouldRender {
+            self.properties
+                .events
+                .borrow_mut()
+                .push((self.properties.id, true));
+            ShouldRender::No
+        }
+
+        fn focus_lost(&mut self) -> ShouldRender {
+            self.properties
+                .events
+                .borrow_mut()
+                .push((self.properties.id, false));
+            ShouldRender::No
+        }
+    }
+
+    #[test]
+    fn tab_cycles_focus_through_focusable_components() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let field = |id| {
+            Item::auto(Field::with(FieldProperties {
+                id,
+                events: events.clone(),
+            }))
+        };
+        let mut app = TestBackend::new(
+            Size::new(10, 3),
+            Layout::column([
+                field(0),
+                Item::auto(Text::with(TextProperties::new().content("not focusable"))),
+                field(1),
+            ]),
+        );
+        assert_eq!(*events.borrow(), [(0, true)]);
+
+        app.keys([KeyCode::Tab, KeyCode::Tab, KeyCode::BackTab]);
+        assert_eq!(
+            events.borrow()[1..],
+            [
+                (0, false),
+                (1, true),
+                (1, false),
+                (0, true),
+                (0, false),
+                (1, true)
+            ]
+        );
+
+        // Focus stays released until moved again
+        events.borrow_mut().clear();
+        app.keys([KeyCode::Esc, KeyCode::Char('x')]);
+        assert_eq!(*events.borrow(), [(1, false)]);
+        app.key(KeyCode::Tab);
+        assert_eq!(*events.borrow(), [(1, false), (0, true)]);
+    }
+
+    type Log = Rc<RefCell<Vec<&'static str>>>;
+
+    struct Parent {
+        log: Log,
+    }
+
+    impl Component for Parent {
+        type Message = &'static str;
+        type Properties = Log;
+
+        fn create(log: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self { log }
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            self.log.borrow_mut().push(message);
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Child::with(self.log.clone())
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("esc", [KeyEvent::from(KeyCode::Esc)], || "parent:esc");
+            bindings.add("x", [KeyEvent::from(KeyCode::Char('x'))], || "parent:x");
+            bindings.add("p", [KeyEvent::from(KeyCode::Char('p'))], || "parent:p");
+            bindings.paste(|text: &str| (text == "parent").then(|| "parent:paste"));
+        }
+    }
+
+    struct Child {
+        log: Log,
+    }
+
+    impl Component for Child {
+        type Message = &'static str;
+        type Properties = Log;
+
+        fn create(log: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self { log }
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            self.log.borrow_mut().push(message);
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focusable(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("esc", [KeyEvent::from(KeyCode::Esc)], || "child:esc");
+            bindings.add("x", [KeyEvent::from(KeyCode::Char('x'))], |_: &Self| None);
+        }
+    }
+
+    #[test]
+    fn keys_bubble_from_focused_component_to_ancestors() {
+        let log = Log::default();
+        let mut app = TestBackend::new(Size::new(10, 3), Parent::with(log.clone()));
+
+        // Consumed by the child
+        app.key(KeyCode::Esc);
+        // The child declines to handle the key by returning `None`
+        app.key(KeyCode::Char('x'));
+        // Not bound by the child
+        app.key(KeyCode::Char('p'));
+        assert_eq!(*log.borrow(), ["child:esc", "parent:x", "parent:p"]);
+    }
+
+    #[test]
+    fn pasted_text_bubbles_to_ancestors_or_is_replayed_as_keys() {
+        let log = Log::default();
+        let mut app = TestBackend::new(Size::new(10, 3), Parent::with(log.clone()));
+
+        // The focused child has no paste handler, the parent accepts the text
+        app.input(Event::Paste("parent".into()));
+        // Declined by the parent, replayed as key presses
+        app.input(Event::Paste("xp".into()));
+        assert_eq!(*log.borrow(), ["parent:paste", "parent:x", "parent:p"]);
+    }
+
+    /// A controlled field without a paste handler. Each typed character is
+    /// appended to the content it was last rendered with.
+    struct Typed {
+        properties: TypedProperties,
+    }
+
+    struct TypedProperties {
+        content: String,
+        on_change: Callback<String>,
+    }
+
+    impl Component for Typed {
+        type Message = char;
+        type Properties = TypedProperties;
+
+        fn create(properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self { properties }
+        }
+
+        fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+            self.properties = properties;
+            ShouldRender::Yes
+        }
+
+        fn update(&mut self, character: Self::Message) -> ShouldRender {
+            let mut content = self.properties.content.clone();
+            content.push(character);
+            self.properties.on_change.emit(content);
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(self.properties.content.clone()))
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("type", AnyCharacter, |keys: &[KeyEvent]| match keys {
+                &[KeyEvent {
+                    code: KeyCode::Char(character),
+                    ..
+                }] => Some(character),
+                _ => None,
+            });
+        }
+    }
+
+    /// Owns the content of a `Typed` field.
+    struct Form {
+        content: String,
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Form {
+        type Message = String;
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self {
+                content: String::new(),
+                link,
+            }
+        }
+
+        fn update(&mut self, content: Self::Message) -> ShouldRender {
+            self.content = content;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            Typed::with(TypedProperties {
+                content: self.content.clone(),
+                on_change: self.link.callback(|content| content),
+            })
+        }
+    }
+
+    #[test]
+    fn pasted_keys_see_the_properties_updated_by_the_previous_key() {
+        let mut app = TestBackend::new(Size::new(10, 1), Form::with(()));
+        app.input(Event::Paste("abc".into()));
+        app.assert_lines(&["abc"]);
+    }
+
+    #[test]
+    fn commands_bound_only_to_any_character_are_not_runnable_by_name() {
+        let mut app = TestBackend::new(Size::new(10, 1), Form::with(()));
+        let commands = app.app().commands();
+        let command = commands
+            .iter()
+            .find(|command| command.name == "type")
+            .unwrap();
+        assert!(command.reachable);
+        assert!(!command.runnable_by_name());
+
+        // Without a key the command declines to run
+        assert!(!app.app().run_command("type"));
+        assert!(!app.app().poll_state().dirty());
+        app.assert_lines(&[""]);
+    }
+
+    #[test]
+    fn keymap_overrides_replace_component_bindings() {
+        let log = Log::default();
+        let mut app = TestBackend::new(Size::new(10, 3), Parent::with(log.clone()));
+        app.app()
+            .set_keymap_overrides("[Child]\nesc = C-g".parse().unwrap());
+
+        app.key(KeyCode::Esc)
+            .key(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL));
+        assert_eq!(*log.borrow(), ["parent:esc", "child:esc"]);
+    }
+
+    #[test]
+    fn commands_can_be_listed_and_run_by_name() {
+        let log = Log::default();
+        let mut app = TestBackend::new(Size::new(10, 3), Parent::with(log.clone()));
+
+        let commands = app.app().commands();
+        let summary: Vec<_> = commands
+            .iter()
+            .map(|command| {
+                (
+                    command.component.rsplit("::").next().unwrap(),
+                    command.name.as_ref(),
+                    command
+                        .keys
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>(),
+                    command.reachable,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Child", "esc", vec!["ESC".to_owned()], true),
+                ("Child", "x", vec!["x".to_owned()], true),
+                ("Parent", "esc", vec!["ESC".to_owned()], true),
+                ("Parent", "x", vec!["x".to_owned()], true),
+                ("Parent", "p", vec!["p".to_owned()], true),
+            ]
+        );
+
+        assert!(app.app().run_command("esc"));
+        assert!(app.app().run_command("x"));
+        assert!(app.app().run_command("p"));
+        assert!(!app.app().run_command("missing"));
+        assert_eq!(*log.borrow(), ["child:esc", "parent:x", "parent:p"]);
+    }
+
+    struct Pinger {
+        properties: PingerProperties,
+        link: ComponentLink<Self>,
+    }
+
+    struct PingerProperties {
+        name: &'static str,
+        inner: bool,
+        log: Log,
+    }
+
+    enum PingerMessage {
+        Ping,
+        Run { outside: bool },
+    }
+
+    impl Component for Pinger {
+        type Message = PingerMessage;
+        type Properties = PingerProperties;
+
+        fn create(properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self { properties, link }
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                PingerMessage::Ping => self.properties.log.borrow_mut().push(self.properties.name),
+                PingerMessage::Run { outside: false } => self.link.run_command("ping"),
+                PingerMessage::Run { outside: true } => self.link.run_command_outside("ping"),
+            }
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            if self.properties.inner {
+                Pinger::with(PingerProperties {
+                    name: "inner",
+                    inner: false,
+                    log: self.properties.log.clone(),
+                })
+            } else {
+                Canvas::new(Size::new(1, 1)).into()
+            }
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.command("ping", || PingerMessage::Ping);
+            bindings.add("run", [KeyEvent::from(KeyCode::Char('r'))], || {
+                PingerMessage::Run { outside: false }
+            });
+            bindings.add("run-outside", [KeyEvent::from(KeyCode::Char('o'))], || {
+                PingerMessage::Run { outside: true }
+            });
+        }
+    }
+
+    #[test]
+    fn links_run_commands_on_themselves_or_outside() {
+        let log = Log::default();
+        let mut app = TestBackend::new(
+            Size::new(1, 1),
+            Pinger::with(PingerProperties {
+                name: "outer",
+                inner: true,
+                log: log.clone(),
+            }),
+        );
+        app.key(KeyCode::Char('r')).key(KeyCode::Char('o'));
+        assert_eq!(*log.borrow(), ["inner", "outer"]);
+    }
+
+    /// Counts key presses of `x` and, if clickable, mouse clicks.
+    struct Pressable {
+        name: &'static str,
+        clickable: bool,
+        presses: usize,
+    }
+
+    impl Component for Pressable {
+        type Message = ();
+        type Properties = (&'static str, bool);
+
+        fn create(
+            (name, clickable): Self::Properties,
+            _frame: Rect,
+            _link: ComponentLink<Self>,
+        ) -> Self {
+            Self {
+                name,
+                clickable,
+                presses: 0,
+            }
+        }
+
+        fn update(&mut self, _message: Self::Message) -> ShouldRender {
+            self.presses += 1;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(format!("{}{}", self.name, self.presses)))
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if self.clickable {
+                bindings.mouse(|_event: MouseEvent| Some(()));
+            }
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("press", [KeyEvent::from(KeyCode::Char('x'))], || ());
+        }
+    }
+
+    struct Anchor;
+
+    impl Component for Anchor {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self
+        }
+
+        fn view(&self) -> Layout {
+            // The popup overflows the anchor's frame, over a later sibling
+            Layout::stack([
+                Layer::new(Pressable::with(("anchor", false))),
+                Layer::new(Pressable::with(("popup", false)))
+                    .at(Rect::new(Position::new(5, 1), Size::new(6, 1))),
+            ])
+        }
+    }
+
+    #[test]
+    fn overlays_are_drawn_on_top_and_take_input() {
+        let mut app = TestBackend::new(
+            Size::new(12, 2),
+            Layout::column([
+                Item::fixed(1)(Anchor::with(())),
+                Item::auto(Pressable::with(("base", true))),
+            ]),
+        );
+        app.assert_lines(&["anchor0", "base0popup0"]);
+
+        // Only the focused components of the topmost overlay receive keys
+        app.key(KeyCode::Char('x'));
+        app.assert_lines(&["anchor0", "base0popup1"]);
+
+        // The overlay hides the component under it from the mouse
+        let click = |x, y| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                position: Position::new(x, y),
+                modifiers: KeyModifiers::empty(),
+            })
+        };
+        app.input(click(6, 1));
+        app.assert_lines(&["anchor0", "base0popup1"]);
+        app.input(click(1, 1));
+        app.assert_lines(&["anchor0", "base1popup1"]);
+    }
+
+    struct Goto {
+        log: Log,
+    }
+
+    impl Component for Goto {
+        type Message = &'static str;
+        type Properties = Log;
+
+        fn create(log: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self { log }
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            self.log.borrow_mut().push(message);
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            let key = |char| KeyEvent::from(KeyCode::Char(char));
+            bindings.set_sequence_timeout(Some(Duration::from_millis(500)));
+            bindings.add("g", [key('g')], || "g");
+            bindings.add("g-g", [key('g'), key('g')], || "g g");
+            bindings.add("x", [key('x')], || "x");
+            bindings.add("esc", [KeyEvent::from(KeyCode::Esc)], || "esc");
+            bindings.add(
+                "alt-x",
+                [KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT)],
+                || "alt-x",
+            );
+            bindings.mouse(|_event: MouseEvent| Some("click"));
+            bindings.paste(|_text: &str| Some("paste"));
+        }
+    }
+
+    #[test]
+    fn pending_sequences_resolve_after_timeout() {
+        let log = Log::default();
+        let mut app = TestBackend::new(Size::new(10, 3), Goto::with(log.clone()));
+
+        app.key(KeyCode::Char('g'));
+        let deadline = app.app().next_deadline().expect("`g` is ambiguous");
+        app.app()
+            .handle_timeout(deadline - Duration::from_millis(1));
+        assert!(log.borrow().is_empty());
+        app.expire_timeouts();
+        assert_eq!(app.app().next_deadline(), None);
+
+        app.keys([KeyCode::Char('g'), KeyCode::Char('g')]);
+        // A key which doesn't continue the sequence resolves it early
+        app.keys([KeyCode::Char('g'), KeyCode::Char('x')]);
+        assert_eq!(*log.borrow(), ["g", "g g", "g", "x"]);
+        assert_eq!(app.app().next_deadline(), None);
+
+        // `Esc` followed by another key in time is an `Alt` chord
+        log.borrow_mut().clear();
+        app.app().set_escape_timeout(Some(Duration::from_secs(60)));
+        app.keys([KeyCode::Esc, KeyCode::Char('x'), KeyCode::Esc]);
+        assert!(app.app().next_deadline().is_some());
+        app.expire_timeouts();
+        assert_eq!(*log.borrow(), ["alt-x", "esc"]);
+
+        // A pending `Esc` is delivered before a paste or a mouse event
+        log.borrow_mut().clear();
+        let click = Event::Mouse(MouseEvent::new(
+            MouseEventKind::Down(MouseButton::Left),
+            Position::new(0, 0),
+            KeyModifiers::empty(),
+        ));
+        app.key(KeyCode::Esc).input(Event::Paste("text".into()));
+        app.key(KeyCode::Esc).input(click);
+        assert_eq!(*log.borrow(), ["esc", "paste", "esc", "click"]);
+        assert_eq!(app.app().next_deadline(), None);
+    }
+
     #[test]
     fn sizes() {
         eprintln!(
@@ -1,14 +1,16 @@
-use smallvec::{smallvec, SmallVec};
+use smallvec::SmallVec;
 use std::{
     any::{Any, TypeId},
     borrow::Cow,
     collections::hash_map::HashMap,
     fmt,
     marker::PhantomData,
+    str::FromStr,
+    time::Duration,
 };
 
 use super::{Component, DynamicMessage};
-use crate::terminal::{KeyCode, KeyEvent};
+use crate::terminal::{KeyEvent, MouseEvent, ParseKeyError};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct CommandId(usize);
@@ -16,23 +18,30 @@ pub struct CommandId(usize);
 #[derive(Clone, Debug, PartialEq)]
 pub enum NamedBindingQuery {
     Match(Cow<'static, str>),
-    PrefixOf(SmallVec<[Cow<'static, str>; 4]>),
+    PrefixOf(Vec<Continuation>),
 }
 
 impl NamedBindingQuery {
-    pub fn new(keymap: &Keymap, query: &BindingQuery) -> Self {
+    /// Creates a named query from the result of checking the `keys` pressed
+    /// so far against a keymap.
+    pub fn new(keymap: &Keymap, query: &BindingQuery, keys: &[KeyEvent]) -> Self {
         match query {
             BindingQuery::Match(command_id) => Self::Match(keymap.names[command_id.0].clone()),
-            BindingQuery::PrefixOf(commands) => Self::PrefixOf(
-                commands
-                    .iter()
-                    .map(|command_id| keymap.names[command_id.0].clone())
-                    .collect(),
-            ),
+            BindingQuery::PrefixOf(_) => Self::PrefixOf(keymap.continuations(keys)),
         }
     }
 }
 
+/// A key binding that could complete a pending key sequence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Continuation {
+    /// The keys left to press after the pending ones. The first key is the
+    /// next key to press.
+    pub keys: SmallVec<[KeyEvent; 8]>,
+    /// The name of the command bound to the key sequence.
+    pub command: Cow<'static, str>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum BindingQuery {
     Match(CommandId),
@@ -55,10 +64,48 @@ impl BindingQuery {
     }
 }
 
+type KeyTable = HashMap<KeyPattern, BindingQuery>;
+
+/// Identifies a binding layer of a [`Keymap`](struct.Keymap.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LayerId(usize);
+
+#[derive(Debug)]
+struct KeymapLayer {
+    name: Cow<'static, str>,
+    priority: i32,
+    active: bool,
+    keymap: KeyTable,
+}
+
+/// Maps key sequences to commands.
+///
+/// Besides the bindings that are always active, a keymap can have named
+/// layers of bindings which can be switched on and off, e.g. the modes of a
+/// modal editor. Active layers are checked in order of decreasing priority
+/// (layers with equal priority in the order they were added), followed by the
+/// bindings outside of any layer. The first one that matches or is a prefix of
+/// the pressed keys wins. Bindings can overlap across layers, but not within
+/// a single layer, unless the keymap has a sequence timeout.
+///
+/// Each layer has its own command namespace: commands added to different
+/// layers with [`add_layer_command`](#method.add_layer_command) are distinct,
+/// even when they share a name.
+///
+/// With a [sequence timeout](#method.set_sequence_timeout), a binding can be a
+/// prefix of another one, e.g. both `g` and `g g` can be bound. Pressing `g`
+/// waits for the next key and runs the command bound to `g` only if no other
+/// key is pressed before the timeout expires.
 #[derive(Debug, Default)]
 pub struct Keymap {
     names: Vec<Cow<'static, str>>,
-    keymap: HashMap<KeyPattern, BindingQuery>,
+    /// The layer each command was added to, `None` for commands outside of
+    /// any layer.
+    command_layers: Vec<Option<LayerId>>,
+    keymap: KeyTable,
+    layers: Vec<KeymapLayer>,
+    sequence_timeout: Option<Duration>,
+    timeout_bindings: HashMap<(Option<LayerId>, KeyPattern), CommandId>,
 }
 
 impl Keymap {
@@ -71,7 +118,22 @@ impl Keymap {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.keymap.is_empty()
+        self.keymap.is_empty() && self.layers.iter().all(|layer| layer.keymap.is_empty())
+    }
+
+    /// Sets how long to wait for the next key of a pending key sequence. When
+    /// the timeout expires, the command bound to the keys pressed so far runs
+    /// if there is one, otherwise the keys are discarded. `None` (the default)
+    /// waits indefinitely.
+    ///
+    /// The timeout has to be set before adding bindings that are a prefix of
+    /// one another.
+    pub fn set_sequence_timeout(&mut self, timeout: Option<Duration>) {
+        self.sequence_timeout = timeout;
+    }
+
+    pub fn sequence_timeout(&self) -> Option<Duration> {
+        self.sequence_timeout
     }
 
     pub fn add(
@@ -85,89 +147,479 @@ impl Keymap {
     }
 
     pub fn add_command(&mut self, name: impl Into<Cow<'static, str>>) -> (CommandId, bool) {
-        let name = name.into();
-        let (command_id, is_new_command) = self
-            .names
+        self.add_command_in(None, name.into())
+    }
+
+    /// Adds a command to the namespace of a layer. Returns the existing
+    /// command if the layer already has one with the same name.
+    pub fn add_layer_command(
+        &mut self,
+        layer_id: LayerId,
+        name: impl Into<Cow<'static, str>>,
+    ) -> (CommandId, bool) {
+        self.add_command_in(Some(layer_id), name.into())
+    }
+
+    /// Returns the id of a command given its name. If several layers have a
+    /// command with that name, the one in the active layer with the highest
+    /// priority wins, followed by the command outside of any layer.
+    pub fn command_id(&self, name: &str) -> Option<CommandId> {
+        let active_layers = self.active_layer_ids();
+        self.commands()
+            .filter(|(_, existing)| *existing == name)
+            .min_by_key(|(command_id, _)| match self.command_layers[command_id.0] {
+                Some(layer_id) => active_layers
+                    .iter()
+                    .position(|active_id| *active_id == layer_id)
+                    .unwrap_or(usize::MAX),
+                None => active_layers.len(),
+            })
+            .map(|(command_id, _)| command_id)
+    }
+
+    /// Returns the id of a command given its name and the layer it was added
+    /// to, or `None` for a command outside of any layer.
+    pub fn layer_command_id(&self, layer_id: Option<LayerId>, name: &str) -> Option<CommandId> {
+        self.commands()
+            .find(|(command_id, existing)| {
+                *existing == name && self.command_layers[command_id.0] == layer_id
+            })
+            .map(|(command_id, _)| command_id)
+    }
+
+    /// Returns the ids and names of all commands in the keymap.
+    pub fn commands(&self) -> impl Iterator<Item = (CommandId, &str)> {
+        self.names
             .iter()
             .enumerate()
-            .find(|(_index, existing)| **existing == name)
-            .map(|(index, _)| (CommandId(index), false))
-            .unwrap_or_else(|| (CommandId(self.names.len()), true));
-        if is_new_command {
-            self.names.push(name);
+            .map(|(index, name)| (CommandId(index), name.as_ref()))
+    }
+
+    /// Returns the key patterns bound to a command, outside of any layer or
+    /// in an active layer.
+    pub fn command_keys(&self, command_id: CommandId) -> Vec<KeyPattern> {
+        let active_layers = self.active_layer_ids();
+        let keymaps = std::iter::once(&self.keymap).chain(
+            active_layers
+                .iter()
+                .map(|layer_id| &self.layers[layer_id.0].keymap),
+        );
+        let mut keys: Vec<KeyPattern> = keymaps
+            .flat_map(|keymap| keymap.iter())
+            .filter(|(_, query)| query.matches() == Some(command_id))
+            .map(|(pattern, _)| pattern.clone())
+            .chain(
+                self.timeout_bindings
+                    .iter()
+                    .filter(|((layer_id, _), other_command_id)| {
+                        **other_command_id == command_id
+                            && layer_id.map_or(true, |layer_id| active_layers.contains(&layer_id))
+                    })
+                    .map(|((_, pattern), _)| pattern.clone()),
+            )
+            .collect();
+        keys.sort_by_cached_key(|pattern| pattern.to_string());
+        keys
+    }
+
+    /// Returns the names of all commands in the keymap. Commands of different
+    /// layers can have the same name.
+    pub fn command_names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|name| name.as_ref())
+    }
+
+    /// Adds a named layer of bindings, initially inactive. If a layer with the
+    /// same name exists, its priority is updated and its id returned.
+    pub fn add_layer(&mut self, name: impl Into<Cow<'static, str>>, priority: i32) -> LayerId {
+        let name = name.into();
+        match self.layer_id(&name) {
+            Some(layer_id) => {
+                self.layers[layer_id.0].priority = priority;
+                layer_id
+            }
+            None => {
+                self.layers.push(KeymapLayer {
+                    name,
+                    priority,
+                    active: false,
+                    keymap: KeyTable::new(),
+                });
+                LayerId(self.layers.len() - 1)
+            }
         }
-        (command_id, is_new_command)
     }
 
+    /// Returns the id of a layer given its name.
+    pub fn layer_id(&self, name: &str) -> Option<LayerId> {
+        self.layers
+            .iter()
+            .position(|layer| layer.name == name)
+            .map(LayerId)
+    }
+
+    pub fn layer_name(&self, layer_id: LayerId) -> &str {
+        &self.layers[layer_id.0].name
+    }
+
+    pub fn set_layer_active(&mut self, layer_id: LayerId, active: bool) {
+        self.layers[layer_id.0].active = active;
+    }
+
+    pub fn is_layer_active(&self, layer_id: LayerId) -> bool {
+        self.layers[layer_id.0].active
+    }
+
+    /// Returns the names of the active layers, highest priority first.
+    pub fn active_layers(&self) -> impl Iterator<Item = &str> {
+        self.active_layer_ids()
+            .into_iter()
+            .map(move |layer_id| self.layer_name(layer_id))
+    }
+
+    /// Returns the bindings which start with the given key sequence (but are
+    /// longer), in the order their commands were added. Only the bindings of
+    /// the layer that the key sequence resolves to are returned.
+    pub fn continuations(&self, prefix: &[KeyEvent]) -> Vec<Continuation> {
+        let (layer_id, keymap) = match self.resolve(prefix) {
+            Some((layer_id, keymap, BindingQuery::PrefixOf(_))) => (layer_id, keymap),
+            _ => return Vec::new(),
+        };
+        let timeout_bindings = self
+            .timeout_bindings
+            .iter()
+            .filter(|((other_layer_id, _), _)| *other_layer_id == layer_id)
+            .map(|((_, pattern), command_id)| (pattern, *command_id));
+        let mut continuations: Vec<(CommandId, String, Continuation)> = keymap
+            .iter()
+            .filter_map(|(pattern, query)| Some((pattern, query.matches()?)))
+            .chain(timeout_bindings)
+            .filter_map(|(pattern, command_id)| {
+                let keys = pattern.keys()?;
+                if keys.len() <= prefix.len() || !keys.starts_with(prefix) {
+                    return None;
+                }
+                let keys: SmallVec<[KeyEvent; 8]> = keys[prefix.len()..].iter().copied().collect();
+                Some((
+                    command_id,
+                    KeySequenceSlice::from(keys.as_slice()).to_string(),
+                    Continuation {
+                        keys,
+                        command: self.names[command_id.0].clone(),
+                    },
+                ))
+            })
+            .collect();
+        continuations.sort_by(|(left_id, left_keys, _), (right_id, right_keys, _)| {
+            (left_id.0, left_keys).cmp(&(right_id.0, right_keys))
+        });
+        continuations
+            .into_iter()
+            .map(|(_, _, continuation)| continuation)
+            .collect()
+    }
+
+    /// Binds a key pattern to a command.
+    ///
+    /// Panics if the pattern overlaps with an existing binding, i.e. if either
+    /// is a prefix of the other.
     pub fn bind_command(&mut self, command_id: CommandId, pattern: impl Into<KeyPattern>) {
-        let name = &self.names[command_id.0];
-        let pattern = pattern.into();
+        if let Err(conflict) = self.try_bind_command(command_id, pattern) {
+            panic!("{}", conflict);
+        }
+    }
+
+    /// Binds a key pattern to a command, unless the pattern overlaps with an
+    /// existing binding. The keymap is left unchanged on conflict.
+    pub fn try_bind_command(
+        &mut self,
+        command_id: CommandId,
+        pattern: impl Into<KeyPattern>,
+    ) -> Result<(), Box<BindingConflict>> {
+        self.try_bind_command_in(None, command_id, pattern.into())
+    }
+
+    /// Binds a key pattern to a command in a layer.
+    ///
+    /// Panics if the pattern overlaps with an existing binding in the same
+    /// layer, i.e. if either is a prefix of the other.
+    pub fn bind_layer_command(
+        &mut self,
+        layer_id: LayerId,
+        command_id: CommandId,
+        pattern: impl Into<KeyPattern>,
+    ) {
+        if let Err(conflict) = self.try_bind_command_in(Some(layer_id), command_id, pattern.into())
+        {
+            panic!("{}", conflict);
+        }
+    }
+
+    /// Binds a key pattern to a command in a layer, unless the pattern
+    /// overlaps with an existing binding in the same layer.
+    pub fn try_bind_layer_command(
+        &mut self,
+        layer_id: LayerId,
+        command_id: CommandId,
+        pattern: impl Into<KeyPattern>,
+    ) -> Result<(), Box<BindingConflict>> {
+        self.try_bind_command_in(Some(layer_id), command_id, pattern.into())
+    }
+
+    /// Removes all key bindings of a command, in all layers. The command
+    /// itself is kept and can be bound again.
+    pub fn unbind_command(&mut self, command_id: CommandId) {
+        let Self {
+            ref mut keymap,
+            ref mut layers,
+            ref mut timeout_bindings,
+            ..
+        } = *self;
+        timeout_bindings.retain(|_, other_command_id| *other_command_id != command_id);
+
+        let keymaps = std::iter::once((None, keymap)).chain(
+            layers
+                .iter_mut()
+                .enumerate()
+                .map(|(index, layer)| (Some(LayerId(index)), &mut layer.keymap)),
+        );
+        for (layer_id, keymap) in keymaps {
+            keymap.retain(|_, query| match query {
+                BindingQuery::Match(other_command_id) => *other_command_id != command_id,
+                BindingQuery::PrefixOf(prefix_of) => {
+                    prefix_of.retain(|other_command_id| *other_command_id != command_id);
+                    !prefix_of.is_empty()
+                }
+            });
+
+            // Bindings which were a prefix of the removed ones are no longer
+            // ambiguous
+            timeout_bindings.retain(|(other_layer_id, pattern), other_command_id| {
+                if *other_layer_id == layer_id && !keymap.contains_key(pattern) {
+                    keymap.insert(pattern.clone(), BindingQuery::Match(*other_command_id));
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
+
+    /// Returns the layer a command was added to or, for commands added outside
+    /// of any layer, the layer of its first binding. `None` if it is bound
+    /// outside of any layer or not bound at all.
+    pub(crate) fn command_layer(&self, command_id: CommandId) -> Option<LayerId> {
+        if let Some(layer_id) = self.command_layers[command_id.0] {
+            return Some(layer_id);
+        }
+        self.layers
+            .iter()
+            .position(|layer| {
+                layer
+                    .keymap
+                    .values()
+                    .any(|query| query.matches() == Some(command_id))
+            })
+            .map(LayerId)
+            .or_else(|| {
+                self.timeout_bindings
+                    .iter()
+                    .find(|(_, other_command_id)| **other_command_id == command_id)
+                    .and_then(|((layer_id, _), _)| *layer_id)
+            })
+    }
+
+    pub fn check_sequence(&self, keys: &[KeyEvent]) -> Option<&BindingQuery> {
+        self.resolve(keys).map(|(_, _, query)| query)
+    }
+
+    /// Returns the command to run when the sequence timeout expires after
+    /// pressing `keys`, if the keys are bound to a command and also a prefix
+    /// of longer bindings.
+    pub fn check_sequence_timeout(&self, keys: &[KeyEvent]) -> Option<CommandId> {
+        match self.resolve(keys)? {
+            (layer_id, _, BindingQuery::PrefixOf(_)) => self
+                .timeout_bindings
+                .get(&(layer_id, keys.iter().copied().into()))
+                .copied(),
+            _ => None,
+        }
+    }
+
+    fn try_bind_command_in(
+        &mut self,
+        layer_id: Option<LayerId>,
+        command_id: CommandId,
+        pattern: KeyPattern,
+    ) -> Result<(), Box<BindingConflict>> {
+        self.check_conflicts(layer_id, command_id, &pattern)?;
+        let Self {
+            ref mut keymap,
+            ref mut layers,
+            ref mut timeout_bindings,
+            ..
+        } = *self;
+        let keymap = match layer_id {
+            Some(layer_id) => &mut layers[layer_id.0].keymap,
+            None => keymap,
+        };
 
         // Add `BindingQuery::PrefixOf` entries for all prefixes of the key sequence
         if let Some(keys) = pattern.keys() {
             for prefix_len in 0..keys.len() {
                 let prefix = KeyPattern::Keys(keys.iter().copied().take(prefix_len).collect());
-                self.keymap
+                let query = keymap
                     .entry(prefix.clone())
-                    .and_modify(|entry| match entry {
-                        BindingQuery::Match(other_command_id) => panic_on_overlapping_key_bindings(
-                            &pattern,
-                            name,
-                            &prefix,
-                            &self.names[other_command_id.0],
-                        ),
-                        BindingQuery::PrefixOf(prefix_of) => {
-                            prefix_of.push(command_id);
-                        }
-                    })
-                    .or_insert_with(|| BindingQuery::PrefixOf(smallvec![command_id]));
+                    .or_insert_with(|| BindingQuery::PrefixOf(SmallVec::new()));
+
+                // A shorter binding becomes ambiguous, it only runs when the
+                // sequence timeout expires (conflicts are checked beforehand)
+                if let BindingQuery::Match(other_command_id) = *query {
+                    timeout_bindings.insert((layer_id, prefix), other_command_id);
+                    *query = BindingQuery::PrefixOf(SmallVec::new());
+                }
+                if let BindingQuery::PrefixOf(prefix_of) = query {
+                    prefix_of.push(command_id);
+                }
             }
         }
 
-        // Add a `BindingQuery::Match` for the full key sequence
-        self.keymap
-            .entry(pattern.clone())
-            .and_modify(|entry| match entry {
-                BindingQuery::Match(other_command_id) => panic_on_overlapping_key_bindings(
-                    &pattern,
-                    name,
-                    &pattern,
-                    &self.names[other_command_id.0],
-                ),
-                BindingQuery::PrefixOf(prefix_of) => panic_on_overlapping_key_bindings(
-                    &pattern,
-                    name,
-                    &pattern,
-                    &self.names[prefix_of[0].0],
-                ),
-            })
-            .or_insert_with(|| BindingQuery::Match(command_id));
+        // Add a `BindingQuery::Match` for the full key sequence, unless it is
+        // a prefix of longer bindings
+        if let Some(BindingQuery::PrefixOf(_)) = keymap.get(&pattern) {
+            timeout_bindings.insert((layer_id, pattern), command_id);
+        } else {
+            keymap.insert(pattern, BindingQuery::Match(command_id));
+        }
+        Ok(())
     }
 
-    pub fn check_sequence(&self, keys: &[KeyEvent]) -> Option<&BindingQuery> {
-        let pattern: KeyPattern = keys.iter().copied().into();
-        self.keymap
-            .get(&pattern)
-            .or_else(|| match keys {
-                &[KeyEvent {
-                    code: _code,
-                    modifiers: _modifiers,
-                }] => self.keymap.get(&KeyPattern::AnyCharacter),
-                _ => None,
-            })
-            .or_else(|| match keys {
-                &[_, key] | &[key] => self.keymap.get(&KeyPattern::EndsWith([key])),
-                _ => None,
+    fn check_conflicts(
+        &self,
+        layer_id: Option<LayerId>,
+        command_id: CommandId,
+        pattern: &KeyPattern,
+    ) -> Result<(), Box<BindingConflict>> {
+        let keymap = match layer_id {
+            Some(layer_id) => &self.layers[layer_id.0].keymap,
+            None => &self.keymap,
+        };
+        let conflict = |existing_pattern: &KeyPattern, existing_command_id: &CommandId| {
+            Box::new(BindingConflict {
+                pattern: pattern.clone(),
+                command: self.names[command_id.0].clone(),
+                existing_pattern: existing_pattern.clone(),
+                existing_command: self.names[existing_command_id.0].clone(),
             })
+        };
+
+        // Bindings that are a prefix of one another are allowed when there's a
+        // sequence timeout to disambiguate them
+        let allow_prefixes = self.sequence_timeout.is_some();
+        if let Some(keys) = pattern.keys() {
+            for prefix_len in 0..keys.len() {
+                let prefix = KeyPattern::Keys(keys.iter().copied().take(prefix_len).collect());
+                if let Some(BindingQuery::Match(other_command_id)) = keymap.get(&prefix) {
+                    if !allow_prefixes {
+                        return Err(conflict(&prefix, other_command_id));
+                    }
+                }
+            }
+        }
+
+        match keymap.get(pattern) {
+            Some(BindingQuery::Match(other_command_id)) => Err(conflict(pattern, other_command_id)),
+            Some(BindingQuery::PrefixOf(prefix_of)) => {
+                match self.timeout_bindings.get(&(layer_id, pattern.clone())) {
+                    Some(other_command_id) => Err(conflict(pattern, other_command_id)),
+                    None if allow_prefixes => Ok(()),
+                    None => Err(conflict(pattern, &prefix_of[0])),
+                }
+            }
+            None => Ok(()),
+        }
     }
+
+    fn add_command_in(
+        &mut self,
+        layer_id: Option<LayerId>,
+        name: Cow<'static, str>,
+    ) -> (CommandId, bool) {
+        match self.layer_command_id(layer_id, &name) {
+            Some(command_id) => (command_id, false),
+            None => {
+                self.names.push(name);
+                self.command_layers.push(layer_id);
+                (CommandId(self.names.len() - 1), true)
+            }
+        }
+    }
+
+    fn active_layer_ids(&self) -> SmallVec<[LayerId; 4]> {
+        let mut layer_ids: SmallVec<[LayerId; 4]> = (0..self.layers.len())
+            .filter(|&index| self.layers[index].active)
+            .map(LayerId)
+            .collect();
+        // The sort is stable, layers with the same priority stay in the order
+        // they were added
+        layer_ids.sort_by_key(|layer_id| std::cmp::Reverse(self.layers[layer_id.0].priority));
+        layer_ids
+    }
+
+    /// Finds the first keymap (active layers by priority, then the bindings
+    /// outside of any layer) with a binding for the key sequence.
+    fn resolve(&self, keys: &[KeyEvent]) -> Option<(Option<LayerId>, &KeyTable, &BindingQuery)> {
+        self.active_layer_ids()
+            .into_iter()
+            .map(|layer_id| (Some(layer_id), &self.layers[layer_id.0].keymap))
+            .chain(std::iter::once((None, &self.keymap)))
+            .find_map(|(layer_id, keymap)| Some((layer_id, keymap, check_keymap(keymap, keys)?)))
+    }
+}
+
+fn check_keymap<'a>(keymap: &'a KeyTable, keys: &[KeyEvent]) -> Option<&'a BindingQuery> {
+    let pattern: KeyPattern = keys.iter().copied().into();
+    keymap
+        .get(&pattern)
+        .or_else(|| match keys {
+            &[KeyEvent {
+                code: _code,
+                modifiers: _modifiers,
+            }] => keymap.get(&KeyPattern::AnyCharacter),
+            _ => None,
+        })
+        .or_else(|| match keys {
+            &[_, key] | &[key] => keymap.get(&KeyPattern::EndsWith([key])),
+            _ => None,
+        })
 }
 
 #[allow(clippy::type_complexity)]
-struct DynamicCommandFn(Box<dyn Fn(&dyn Any, &[KeyEvent]) -> Option<DynamicMessage>>);
+struct DynamicCommandFn {
+    call: Box<dyn Fn(&dyn Any, &[KeyEvent]) -> Option<DynamicMessage>>,
+    bubbles_when_unhandled: bool,
+}
 
 impl fmt::Debug for DynamicCommandFn {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(formatter, "CommandFn@{:?})", &self.0 as *const _)
+        write!(formatter, "CommandFn@{:?})", &self.call as *const _)
+    }
+}
+
+#[allow(clippy::type_complexity)]
+struct DynamicMouseFn(Box<dyn Fn(&dyn Any, MouseEvent) -> Option<DynamicMessage>>);
+
+impl fmt::Debug for DynamicMouseFn {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "MouseFn@{:?})", &self.0 as *const _)
+    }
+}
+
+#[allow(clippy::type_complexity)]
+struct DynamicPasteFn(Box<dyn Fn(&dyn Any, &str) -> Option<DynamicMessage>>);
+
+impl fmt::Debug for DynamicPasteFn {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "PasteFn@{:?})", &self.0 as *const _)
     }
 }
 
@@ -175,7 +627,10 @@ impl fmt::Debug for DynamicCommandFn {
 pub(crate) struct DynamicBindings {
     keymap: Keymap,
     commands: Vec<DynamicCommandFn>,
+    mouse: Option<DynamicMouseFn>,
+    paste: Option<DynamicPasteFn>,
     focused: bool,
+    focusable: bool,
     notify: bool,
     type_id: TypeId,
 }
@@ -185,7 +640,10 @@ impl DynamicBindings {
         Self {
             keymap: Keymap::new(),
             commands: Vec::new(),
+            mouse: None,
+            paste: None,
             focused: false,
+            focusable: false,
             notify: false,
             type_id: TypeId::of::<ComponentT>(),
         }
@@ -196,6 +654,11 @@ impl DynamicBindings {
         &self.keymap
     }
 
+    #[inline]
+    pub fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
+
     #[inline]
     pub fn set_focus(&mut self, focused: bool) {
         self.focused = focused;
@@ -206,6 +669,16 @@ impl DynamicBindings {
         self.focused
     }
 
+    #[inline]
+    pub fn set_focusable(&mut self, focusable: bool) {
+        self.focusable = focusable;
+    }
+
+    #[inline]
+    pub fn focusable(&self) -> bool {
+        self.focusable
+    }
+
     #[inline]
     pub fn set_notify(&mut self, notify: bool) {
         self.notify = notify;
@@ -231,19 +704,42 @@ impl DynamicBindings {
         &mut self,
         name: impl Into<Cow<'static, str>>,
         command_fn: impl CommandFn<ComponentT, VARIANT> + 'static,
+    ) -> CommandId {
+        self.add_command_in(None, name, command_fn)
+    }
+
+    /// Adds a command to the namespace of a layer, see
+    /// [`Keymap::add_layer_command`](struct.Keymap.html#method.add_layer_command).
+    pub fn add_layer_command<ComponentT: Component, const VARIANT: usize>(
+        &mut self,
+        layer_id: LayerId,
+        name: impl Into<Cow<'static, str>>,
+        command_fn: impl CommandFn<ComponentT, VARIANT> + 'static,
+    ) -> CommandId {
+        self.add_command_in(Some(layer_id), name, command_fn)
+    }
+
+    fn add_command_in<ComponentT: Component, const VARIANT: usize>(
+        &mut self,
+        layer_id: Option<LayerId>,
+        name: impl Into<Cow<'static, str>>,
+        command_fn: impl CommandFn<ComponentT, VARIANT> + 'static,
     ) -> CommandId {
         assert_eq!(self.type_id, TypeId::of::<ComponentT>());
 
-        let (command_id, is_new_command) = self.keymap.add_command(name);
-        let dyn_command_fn =
-            DynamicCommandFn(Box::new(move |erased: &dyn Any, keys: &[KeyEvent]| {
+        let (command_id, is_new_command) = self.keymap.add_command_in(layer_id, name.into());
+        let bubbles_when_unhandled = bubbles_when_unhandled(&command_fn);
+        let dyn_command_fn = DynamicCommandFn {
+            call: Box::new(move |erased: &dyn Any, keys: &[KeyEvent]| {
                 let component = erased
                     .downcast_ref()
                     .expect("Incorrect `Component` type when downcasting");
                 command_fn
                     .call(component, keys)
                     .map(|message| DynamicMessage(Box::new(message)))
-            }));
+            }),
+            bubbles_when_unhandled,
+        };
         if is_new_command {
             self.commands.push(dyn_command_fn);
         } else {
@@ -257,6 +753,15 @@ impl DynamicBindings {
         self.keymap.bind_command(command_id, keys);
     }
 
+    pub fn bind_layer_command(
+        &mut self,
+        layer_id: LayerId,
+        command_id: CommandId,
+        keys: impl Into<KeyPattern>,
+    ) {
+        self.keymap.bind_layer_command(layer_id, command_id, keys);
+    }
+
     pub fn execute_command<ComponentT: Component>(
         &self,
         component: &ComponentT,
@@ -265,7 +770,94 @@ impl DynamicBindings {
     ) -> Option<DynamicMessage> {
         assert_eq!(self.type_id, TypeId::of::<ComponentT>());
 
-        (self.commands[id.0].0)(component, keys)
+        (self.commands[id.0].call)(component, keys)
+    }
+
+    /// Whether a key sequence bound to the command should bubble up to
+    /// ancestor components when the command doesn't return a message.
+    #[inline]
+    pub fn command_bubbles_when_unhandled(&self, id: CommandId) -> bool {
+        self.commands[id.0].bubbles_when_unhandled
+    }
+
+    pub fn set_mouse_handler<ComponentT: Component, const VARIANT: usize>(
+        &mut self,
+        mouse_fn: impl MouseFn<ComponentT, VARIANT> + 'static,
+    ) {
+        assert_eq!(self.type_id, TypeId::of::<ComponentT>());
+
+        self.mouse = Some(DynamicMouseFn(Box::new(
+            move |erased: &dyn Any, event: MouseEvent| {
+                let component = erased
+                    .downcast_ref()
+                    .expect("Incorrect `Component` type when downcasting");
+                mouse_fn
+                    .call(component, event)
+                    .map(|message| DynamicMessage(Box::new(message)))
+            },
+        )));
+    }
+
+    #[inline]
+    pub fn clear_mouse_handler(&mut self) {
+        self.mouse = None;
+    }
+
+    #[inline]
+    pub fn has_mouse_handler(&self) -> bool {
+        self.mouse.is_some()
+    }
+
+    pub fn execute_mouse_handler<ComponentT: Component>(
+        &self,
+        component: &ComponentT,
+        event: MouseEvent,
+    ) -> Option<DynamicMessage> {
+        assert_eq!(self.type_id, TypeId::of::<ComponentT>());
+
+        self.mouse
+            .as_ref()
+            .and_then(|mouse_fn| (mouse_fn.0)(component, event))
+    }
+
+    pub fn set_paste_handler<ComponentT: Component, const VARIANT: usize>(
+        &mut self,
+        paste_fn: impl PasteFn<ComponentT, VARIANT> + 'static,
+    ) {
+        assert_eq!(self.type_id, TypeId::of::<ComponentT>());
+
+        self.paste = Some(DynamicPasteFn(Box::new(
+            move |erased: &dyn Any, text: &str| {
+                let component = erased
+                    .downcast_ref()
+                    .expect("Incorrect `Component` type when downcasting");
+                paste_fn
+                    .call(component, text)
+                    .map(|message| DynamicMessage(Box::new(message)))
+            },
+        )));
+    }
+
+    #[inline]
+    pub fn clear_paste_handler(&mut self) {
+        self.paste = None;
+    }
+
+    #[inline]
+    pub fn has_paste_handler(&self) -> bool {
+        self.paste.is_some()
+    }
+
+    pub fn execute_paste_handler<ComponentT: Component>(
+        &self,
+        component: &ComponentT,
+        text: &str,
+    ) -> Option<DynamicMessage> {
+        assert_eq!(self.type_id, TypeId::of::<ComponentT>());
+
+        self.paste
+            .as_ref()
+            .and_then(|paste_fn| (paste_fn.0)(component, text))
     }
 
     pub fn typed<ComponentT: Component>(
@@ -301,6 +893,9 @@ impl<ComponentT: Component> Bindings<ComponentT> {
         self.bindings.keymap.is_empty()
     }
 
+    /// Marks the component as focused, regardless of the focus chain managed
+    /// by the runtime. The component's key bindings are active for as long as
+    /// it stays focused.
     #[inline]
     pub fn set_focus(&mut self, focused: bool) {
         self.bindings.set_focus(focused)
@@ -311,11 +906,39 @@ impl<ComponentT: Component> Bindings<ComponentT> {
         self.bindings.focused()
     }
 
+    /// Declares whether the component can receive focus from the runtime.
+    ///
+    /// Focusable components form a focus chain in layout order. `Tab` and
+    /// `BackTab` move focus along the chain, unless a focused component binds
+    /// them, and [`ComponentLink`](../struct.ComponentLink.html) can request,
+    /// release or move focus. The key bindings of the focused component are
+    /// active, as if it called `set_focus(true)`.
+    #[inline]
+    pub fn set_focusable(&mut self, focusable: bool) {
+        self.bindings.set_focusable(focusable)
+    }
+
+    #[inline]
+    pub fn focusable(&self) -> bool {
+        self.bindings.focusable()
+    }
+
     #[inline]
     pub fn set_notify(&mut self, notify: bool) {
         self.bindings.set_notify(notify)
     }
 
+    /// Sets how long to wait for the next key of a pending key sequence, see
+    /// [`Keymap::set_sequence_timeout`](struct.Keymap.html#method.set_sequence_timeout).
+    ///
+    /// With a timeout, bindings can be a prefix of one another. For example,
+    /// with `g` and `g g` bound, pressing `g` once runs the first command
+    /// after the timeout, unless `g` is pressed again in the meantime.
+    #[inline]
+    pub fn set_sequence_timeout(&mut self, timeout: Option<Duration>) {
+        self.bindings.keymap.set_sequence_timeout(timeout)
+    }
+
     #[inline]
     pub fn notify(&self) -> bool {
         self.bindings.notify()
@@ -341,23 +964,196 @@ impl<ComponentT: Component> Bindings<ComponentT> {
         BindingBuilder {
             wrapped: self,
             command_id,
+            layer_id: None,
         }
     }
+
+    /// Returns a handle to add bindings to a named layer, creating the layer
+    /// if it doesn't exist. New layers are inactive.
+    ///
+    /// Layers let a component keep several sets of bindings and switch
+    /// between them at runtime, e.g. the "normal" and "insert" modes of a
+    /// modal editor. Active layers take precedence over the bindings added
+    /// directly to `Bindings`, and layers with a higher priority take
+    /// precedence over those with a lower one.
+    ///
+    /// ```
+    /// # use zi::prelude::*;
+    /// # struct Editor { insert_mode: bool }
+    /// # impl Component for Editor {
+    /// #     type Message = bool;
+    /// #     type Properties = ();
+    /// #     fn create(_: (), _: Rect, _: ComponentLink<Self>) -> Self { Self { insert_mode: false } }
+    /// #     fn view(&self) -> Layout { Canvas::new(Size::new(1, 1)).into() }
+    /// fn bindings(&self, bindings: &mut Bindings<Self>) {
+    ///     if bindings.is_empty() {
+    ///         bindings
+    ///             .layer("normal", 0)
+    ///             .add("insert-mode", [KeyEvent::from(KeyCode::Char('i'))], || true);
+    ///         bindings
+    ///             .layer("insert", 0)
+    ///             .add("normal-mode", [KeyEvent::from(KeyCode::Esc)], || false);
+    ///     }
+    ///     bindings.set_layer_active("normal", !self.insert_mode);
+    ///     bindings.set_layer_active("insert", self.insert_mode);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// As with other bindings, layers are usually switched on and off in
+    /// [`Component::bindings`](../trait.Component.html#method.bindings), which
+    /// is called whenever the component is drawn.
+    #[inline]
+    pub fn layer(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        priority: i32,
+    ) -> LayerBindings<'_, ComponentT> {
+        let layer_id = self.bindings.keymap.add_layer(name, priority);
+        LayerBindings {
+            wrapped: self,
+            layer_id,
+        }
+    }
+
+    /// Switches a layer on or off.
+    ///
+    /// Panics if there is no layer with the given name.
+    #[inline]
+    pub fn set_layer_active(&mut self, name: &str, active: bool) {
+        let layer_id = self.layer_id(name);
+        self.bindings.keymap.set_layer_active(layer_id, active);
+    }
+
+    /// Returns `true` if the layer is active.
+    ///
+    /// Panics if there is no layer with the given name.
+    #[inline]
+    pub fn is_layer_active(&self, name: &str) -> bool {
+        self.bindings.keymap.is_layer_active(self.layer_id(name))
+    }
+
+    fn layer_id(&self, name: &str) -> LayerId {
+        self.bindings
+            .keymap
+            .layer_id(name)
+            .unwrap_or_else(|| panic!("No binding layer named `{}`", name))
+    }
+
+    /// Registers a handler for mouse events, replacing any existing one.
+    ///
+    /// Mouse events are delivered to the topmost component under the cursor
+    /// which has a mouse handler. The position of the event is relative to the
+    /// origin of the component's frame. If the handler returns `None`, the
+    /// event is offered to the next component under the cursor, if any.
+    #[inline]
+    pub fn mouse<const VARIANT: usize>(
+        &mut self,
+        mouse_fn: impl MouseFn<ComponentT, VARIANT> + 'static,
+    ) {
+        self.bindings.set_mouse_handler(mouse_fn);
+    }
+
+    /// Removes the mouse handler, if any.
+    #[inline]
+    pub fn clear_mouse(&mut self) {
+        self.bindings.clear_mouse_handler();
+    }
+
+    #[inline]
+    pub fn has_mouse(&self) -> bool {
+        self.bindings.has_mouse_handler()
+    }
+
+    /// Registers a handler for pasted text, replacing any existing one.
+    ///
+    /// Pasted text is offered to focused components with a paste handler as a
+    /// single event. If none of them handles it, the text is delivered one
+    /// character at a time as key events instead.
+    #[inline]
+    pub fn paste<const VARIANT: usize>(
+        &mut self,
+        paste_fn: impl PasteFn<ComponentT, VARIANT> + 'static,
+    ) {
+        self.bindings.set_paste_handler(paste_fn);
+    }
+
+    /// Removes the paste handler, if any.
+    #[inline]
+    pub fn clear_paste(&mut self) {
+        self.bindings.clear_paste_handler();
+    }
+
+    #[inline]
+    pub fn has_paste(&self) -> bool {
+        self.bindings.has_paste_handler()
+    }
 }
 
 #[derive(Debug)]
 pub struct BindingBuilder<'a, ComponentT> {
     wrapped: &'a mut Bindings<ComponentT>,
     command_id: CommandId,
+    layer_id: Option<LayerId>,
 }
 
 impl<ComponentT: Component> BindingBuilder<'_, ComponentT> {
     pub fn with(self, keys: impl Into<KeyPattern>) -> Self {
-        self.wrapped.bindings.bind_command(self.command_id, keys);
+        match self.layer_id {
+            Some(layer_id) => {
+                self.wrapped
+                    .bindings
+                    .bind_layer_command(layer_id, self.command_id, keys)
+            }
+            None => self.wrapped.bindings.bind_command(self.command_id, keys),
+        }
         self
     }
 }
 
+/// Adds bindings to a layer, see [`Bindings::layer`](struct.Bindings.html#method.layer).
+#[derive(Debug)]
+pub struct LayerBindings<'a, ComponentT> {
+    wrapped: &'a mut Bindings<ComponentT>,
+    layer_id: LayerId,
+}
+
+impl<ComponentT: Component> LayerBindings<'_, ComponentT> {
+    #[inline]
+    pub fn add<const VARIANT: usize>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        keys: impl Into<KeyPattern>,
+        command_fn: impl CommandFn<ComponentT, VARIANT> + 'static,
+    ) -> &mut Self {
+        let command_id = self
+            .wrapped
+            .bindings
+            .add_layer_command(self.layer_id, name, command_fn);
+        self.wrapped
+            .bindings
+            .bind_layer_command(self.layer_id, command_id, keys);
+        self
+    }
+
+    #[inline]
+    pub fn command<const VARIANT: usize>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        command_fn: impl CommandFn<ComponentT, VARIANT> + 'static,
+    ) -> BindingBuilder<'_, ComponentT> {
+        let command_id = self
+            .wrapped
+            .bindings
+            .add_layer_command(self.layer_id, name, command_fn);
+        BindingBuilder {
+            wrapped: self.wrapped,
+            command_id,
+            layer_id: Some(self.layer_id),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum KeyPattern {
     AnyCharacter,
@@ -375,6 +1171,28 @@ impl KeyPattern {
     }
 }
 
+/// Parses a key pattern from a sequence of keys separated by spaces, e.g.
+/// `C-x C-f` or `<ctrl-x> <ctrl-f>`. The pattern matching any character is
+/// written as `Char(*)`.
+impl FromStr for KeyPattern {
+    type Err = ParseKeyError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.trim() == "Char(*)" {
+            return Ok(Self::AnyCharacter);
+        }
+
+        let keys = input
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<SmallVec<_>, _>>()?;
+        if keys.is_empty() {
+            return Err(ParseKeyError::new(input));
+        }
+        Ok(Self::Keys(keys))
+    }
+}
+
 impl<IterT: IntoIterator<Item = KeyEvent>> From<IterT> for KeyPattern {
     fn from(keys: IterT) -> Self {
         Self::Keys(keys.into_iter().collect())
@@ -412,9 +1230,24 @@ impl From<EndsWith> for KeyPattern {
 }
 
 pub trait CommandFn<ComponentT: Component, const VARIANT: usize> {
+    /// Whether the keys that triggered the command bubble up to ancestor
+    /// components when `call` returns `None`. This is the case for commands
+    /// that return an `Option`, which can decline to handle a key sequence.
+    /// Commands that don't return a message always consume their keys.
+    const BUBBLES_WHEN_UNHANDLED: bool = true;
+
     fn call(&self, component: &ComponentT, keys: &[KeyEvent]) -> Option<ComponentT::Message>;
 }
 
+#[inline]
+fn bubbles_when_unhandled<ComponentT, FnT, const VARIANT: usize>(_command_fn: &FnT) -> bool
+where
+    ComponentT: Component,
+    FnT: CommandFn<ComponentT, VARIANT>,
+{
+    FnT::BUBBLES_WHEN_UNHANDLED
+}
+
 // Specializations for callbacks that take either a component or slice with keys
 // and return an option
 impl<ComponentT, FnT> CommandFn<ComponentT, 0> for FnT
@@ -455,6 +1288,8 @@ where
     ComponentT: Component,
     FnT: Fn(&ComponentT, &[KeyEvent]) + 'static,
 {
+    const BUBBLES_WHEN_UNHANDLED: bool = false;
+
     #[inline]
     fn call(&self, component: &ComponentT, keys: &[KeyEvent]) -> Option<ComponentT::Message> {
         (self)(component, keys);
@@ -467,6 +1302,8 @@ where
     ComponentT: Component,
     FnT: Fn(&ComponentT) + 'static,
 {
+    const BUBBLES_WHEN_UNHANDLED: bool = false;
+
     #[inline]
     fn call(&self, component: &ComponentT, _keys: &[KeyEvent]) -> Option<ComponentT::Message> {
         (self)(component);
@@ -486,6 +1323,60 @@ where
     }
 }
 
+pub trait MouseFn<ComponentT: Component, const VARIANT: usize> {
+    fn call(&self, component: &ComponentT, event: MouseEvent) -> Option<ComponentT::Message>;
+}
+
+// Specializations for mouse handlers that take the event and optionally the component
+impl<ComponentT, FnT> MouseFn<ComponentT, 0> for FnT
+where
+    ComponentT: Component,
+    FnT: Fn(&ComponentT, MouseEvent) -> Option<ComponentT::Message> + 'static,
+{
+    #[inline]
+    fn call(&self, component: &ComponentT, event: MouseEvent) -> Option<ComponentT::Message> {
+        (self)(component, event)
+    }
+}
+
+impl<ComponentT, FnT> MouseFn<ComponentT, 1> for FnT
+where
+    ComponentT: Component,
+    FnT: Fn(MouseEvent) -> Option<ComponentT::Message> + 'static,
+{
+    #[inline]
+    fn call(&self, _component: &ComponentT, event: MouseEvent) -> Option<ComponentT::Message> {
+        (self)(event)
+    }
+}
+
+pub trait PasteFn<ComponentT: Component, const VARIANT: usize> {
+    fn call(&self, component: &ComponentT, text: &str) -> Option<ComponentT::Message>;
+}
+
+// Specializations for paste handlers that take the text and optionally the component
+impl<ComponentT, FnT> PasteFn<ComponentT, 0> for FnT
+where
+    ComponentT: Component,
+    FnT: Fn(&ComponentT, &str) -> Option<ComponentT::Message> + 'static,
+{
+    #[inline]
+    fn call(&self, component: &ComponentT, text: &str) -> Option<ComponentT::Message> {
+        (self)(component, text)
+    }
+}
+
+impl<ComponentT, FnT> PasteFn<ComponentT, 1> for FnT
+where
+    ComponentT: Component,
+    FnT: Fn(&str) -> Option<ComponentT::Message> + 'static,
+{
+    #[inline]
+    fn call(&self, _component: &ComponentT, text: &str) -> Option<ComponentT::Message> {
+        (self)(text)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeySequenceSlice<'a>(&'a [KeyEvent]);
 
@@ -498,38 +1389,33 @@ impl<'a> From<&'a [KeyEvent]> for KeySequenceSlice<'a> {
 impl<'a> std::fmt::Display for KeySequenceSlice<'a> {
     fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
         for (index, key) in self.0.iter().enumerate() {
-            let _modifiers = key.modifiers;
-            let key = key.code;
-            match key {
-                KeyCode::Char(' ') => write!(formatter, "SPC")?,
-                KeyCode::Enter => write!(formatter, "RET")?,
-                KeyCode::Tab => write!(formatter, "TAB")?,
-                KeyCode::Char(char) => {
-                    // TODO: Print modifiers here as well
-                    write!(formatter, "{}", char)?
-                }
-                KeyCode::F(number) => write!(formatter, "F{}", number)?,
-                KeyCode::Esc => write!(formatter, "ESC")?,
-                key => write!(formatter, "{:?}", key)?,
-            }
-            if index < self.0.len().saturating_sub(1) {
+            if index > 0 {
                 write!(formatter, " ")?;
             }
+            key.fmt(formatter)?;
         }
         Ok(())
     }
 }
 
-fn panic_on_overlapping_key_bindings(
-    new_pattern: &KeyPattern,
-    new_name: &str,
-    existing_pattern: &KeyPattern,
-    existing_name: &str,
-) -> ! {
-    panic!(
-        "Binding `{}` for `{}` is ambiguous as it overlaps with binding `{}` for command `{}`",
-        new_pattern, new_name, existing_pattern, existing_name,
-    );
+/// Error returned when binding a key pattern that overlaps with an existing
+/// binding, i.e. when one of them is a prefix of the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingConflict {
+    pub pattern: KeyPattern,
+    pub command: Cow<'static, str>,
+    pub existing_pattern: KeyPattern,
+    pub existing_command: Cow<'static, str>,
+}
+
+impl fmt::Display for BindingConflict {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "Binding `{}` for `{}` is ambiguous as it overlaps with binding `{}` for command `{}`",
+            self.pattern, self.command, self.existing_pattern, self.existing_command,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -628,4 +1514,170 @@ mod tests {
         controller.execute_command(&Empty, test_command_id, &[]);
         assert!(*called.borrow(), "set-controller wasn't called");
     }
+
+    #[test]
+    fn key_patterns_round_trip_through_strings() {
+        let ctrl = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::CONTROL);
+        let find_file = KeyPattern::from([ctrl('x'), ctrl('f')]);
+        assert_eq!("C-x C-f".parse(), Ok(find_file.clone()));
+        assert_eq!("<ctrl-x>  <Control-f>".parse(), Ok(find_file.clone()));
+        assert_eq!(find_file.to_string(), "C-x C-f");
+
+        for input in [
+            "a",
+            "A",
+            "SPC",
+            "RET",
+            "A-S-Left",
+            "C-A-x",
+            "F12",
+            "ESC :",
+            "C-c PageDown",
+        ] {
+            let pattern: KeyPattern = input.parse().unwrap();
+            assert_eq!(pattern.to_string(), input);
+        }
+        assert_eq!(
+            "<meta-RET>".parse::<KeyPattern>().unwrap().to_string(),
+            "A-RET"
+        );
+        assert_eq!("Char(*)".parse(), Ok(KeyPattern::AnyCharacter));
+        assert_eq!(
+            KeyEvent::new(KeyCode::Char('1'), KeyModifiers::SHIFT).to_string(),
+            "S-1"
+        );
+
+        for input in ["", "C-", "<>", "c-x", "<ctrl-Fn>", "Hyper-x"] {
+            assert!(
+                input.parse::<KeyPattern>().is_err(),
+                "`{}` should not parse",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn key_events_round_trip_with_any_modifiers() {
+        let modifiers = [
+            KeyModifiers::SHIFT,
+            KeyModifiers::CONTROL,
+            KeyModifiers::ALT,
+        ];
+        for bits in 0..(1 << modifiers.len()) {
+            let modifiers = modifiers
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| bits & (1 << index) != 0)
+                .fold(KeyModifiers::empty(), |all, (_, modifier)| all | *modifier);
+            for character in ['a', 'Z', '1', ';', '-', '<'] {
+                let key = KeyEvent::new(KeyCode::Char(character), modifiers);
+                assert_eq!(
+                    key.to_string().parse(),
+                    Ok(key),
+                    "`{}` should parse back to {:?}",
+                    key,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn layers_have_separate_command_namespaces() {
+        let key = |char| KeyEvent::from(KeyCode::Char(char));
+        let called = Rc::new(RefCell::new(Vec::new()));
+        let delete = |layer: &'static str| {
+            let called = Rc::clone(&called);
+            move |_: &Empty| {
+                called.borrow_mut().push(layer);
+                None
+            }
+        };
+        let mut bindings = Bindings::<Empty>::new(DynamicBindings::new::<Empty>());
+        bindings
+            .layer("normal", 0)
+            .add("delete", [key('d'), key('d')], delete("normal"));
+        bindings
+            .layer("visual", 10)
+            .add("delete", [key('d')], delete("visual"));
+
+        bindings.set_layer_active("normal", true);
+        let normal_id = bindings
+            .bindings
+            .keymap()
+            .check_sequence(&[key('d'), key('d')])
+            .and_then(BindingQuery::matches)
+            .unwrap();
+        assert_eq!(
+            bindings.bindings.keymap().command_id("delete"),
+            Some(normal_id)
+        );
+        bindings.bindings.execute_command(&Empty, normal_id, &[]);
+
+        bindings.set_layer_active("visual", true);
+        let visual_id = bindings
+            .bindings
+            .keymap()
+            .check_sequence(&[key('d')])
+            .and_then(BindingQuery::matches)
+            .unwrap();
+        assert_ne!(normal_id, visual_id);
+        assert_eq!(
+            bindings.bindings.keymap().command_id("delete"),
+            Some(visual_id)
+        );
+        bindings.bindings.execute_command(&Empty, visual_id, &[]);
+
+        assert_eq!(*called.borrow(), ["normal", "visual"]);
+    }
+
+    #[test]
+    fn keymap_layers_shadow_bindings_by_priority() {
+        let key = |char| KeyEvent::from(KeyCode::Char(char));
+        let mut keymap = Keymap::new();
+        let quit_id = keymap.add("quit", [key('q')]);
+        let normal = keymap.add_layer("normal", 0);
+        let visual = keymap.add_layer("visual", 10);
+        let delete_line_id = keymap.add_command("delete-line").0;
+        keymap.bind_layer_command(normal, delete_line_id, [key('d'), key('d')]);
+        // Overlaps with `d d` in another layer
+        let delete_selection_id = keymap.add_command("delete-selection").0;
+        keymap.bind_layer_command(visual, delete_selection_id, [key('d')]);
+        let record_id = keymap.add_command("record-macro").0;
+        keymap.bind_layer_command(normal, record_id, [key('q')]);
+
+        // Layers start inactive
+        assert_eq!(keymap.check_sequence(&[key('d')]), None);
+        assert_eq!(
+            keymap.check_sequence(&[key('q')]),
+            Some(&BindingQuery::Match(quit_id))
+        );
+
+        keymap.set_layer_active(normal, true);
+        assert_eq!(
+            keymap.check_sequence(&[key('d'), key('d')]),
+            Some(&BindingQuery::Match(delete_line_id))
+        );
+        assert_eq!(
+            keymap.check_sequence(&[key('q')]),
+            Some(&BindingQuery::Match(record_id))
+        );
+
+        keymap.set_layer_active(visual, true);
+        assert_eq!(
+            keymap.active_layers().collect::<Vec<_>>(),
+            ["visual", "normal"]
+        );
+        assert_eq!(
+            keymap.check_sequence(&[key('d')]),
+            Some(&BindingQuery::Match(delete_selection_id))
+        );
+
+        keymap.add_layer("normal", 20);
+        assert_eq!(
+            keymap.check_sequence(&[key('d')]),
+            Some(&BindingQuery::PrefixOf(smallvec![delete_line_id]))
+        );
+        assert_eq!(keymap.continuations(&[key('d')])[0].command, "delete-line");
+    }
 }
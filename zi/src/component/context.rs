@@ -0,0 +1,225 @@
+//! Values provided by components to their descendants, see
+//! [`ComponentLink::provide`](../struct.ComponentLink.html#method.provide).
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// A provided value. Values are compared when the tree is redrawn, so that
+/// descendants are only re-rendered when a value actually changed.
+trait ContextValue: Any + Send + Sync {
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn eq_value(&self, other: &dyn ContextValue) -> bool;
+}
+
+impl<ValueT: PartialEq + Send + Sync + 'static> ContextValue for ValueT {
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_value(&self, other: &dyn ContextValue) -> bool {
+        other.as_any().downcast_ref::<ValueT>() == Some(self)
+    }
+}
+
+/// Provided values, keyed by their type.
+#[derive(Clone, Default)]
+pub(crate) struct ContextMap(HashMap<TypeId, Arc<dyn ContextValue>>);
+
+impl ContextMap {
+    fn get<ValueT: Send + Sync + 'static>(&self) -> Option<Arc<ValueT>> {
+        self.0
+            .get(&TypeId::of::<ValueT>())
+            .and_then(|value| value.clone().into_any().downcast().ok())
+    }
+
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().all(|(type_id, value)| {
+                other
+                    .0
+                    .get(type_id)
+                    .map_or(false, |other_value| value.eq_value(other_value.as_ref()))
+            })
+    }
+}
+
+#[derive(Default)]
+struct ComponentContexts {
+    /// The values provided by the component's ancestors. Where several
+    /// ancestors provide a value of the same type, the nearest one wins.
+    inherited: Arc<ContextMap>,
+    /// The values provided by the component itself during its latest `view`.
+    provided: ContextMap,
+}
+
+/// The context values of a mounted component, shared with its link.
+#[derive(Clone, Default)]
+pub(crate) struct SharedContexts(Arc<RwLock<ComponentContexts>>);
+
+impl SharedContexts {
+    pub(crate) fn new(inherited: Arc<ContextMap>) -> Self {
+        Self(Arc::new(RwLock::new(ComponentContexts {
+            inherited,
+            provided: ContextMap::default(),
+        })))
+    }
+
+    /// Returns the value of a type provided by the nearest ancestor.
+    pub(crate) fn get<ValueT: Send + Sync + 'static>(&self) -> Option<Arc<ValueT>> {
+        self.0
+            .read()
+            .expect("context lock to not be poisoned")
+            .inherited
+            .get()
+    }
+
+    pub(crate) fn provide<ValueT: PartialEq + Send + Sync + 'static>(&self, value: ValueT) {
+        self.0
+            .write()
+            .expect("context lock to not be poisoned")
+            .provided
+            .0
+            .insert(TypeId::of::<ValueT>(), Arc::new(value));
+    }
+
+    /// Forgets the provided values, before the component provides them again
+    /// in `view`.
+    pub(crate) fn clear_provided(&self) {
+        self.0
+            .write()
+            .expect("context lock to not be poisoned")
+            .provided
+            .0
+            .clear();
+    }
+
+    /// Replaces the inherited values. Returns `true` if any of them changed.
+    pub(crate) fn set_inherited(&self, inherited: Arc<ContextMap>) -> bool {
+        let mut contexts = self.0.write().expect("context lock to not be poisoned");
+        let changed =
+            !Arc::ptr_eq(&contexts.inherited, &inherited) && !contexts.inherited.eq(&inherited);
+        contexts.inherited = inherited;
+        changed
+    }
+
+    /// Returns the values seen by the component's children, i.e. the
+    /// inherited ones overridden by those the component provides.
+    pub(crate) fn for_children(&self) -> Arc<ContextMap> {
+        let contexts = self.0.read().expect("context lock to not be poisoned");
+        if contexts.provided.0.is_empty() {
+            return contexts.inherited.clone();
+        }
+        let mut values = (*contexts.inherited).clone();
+        values.0.extend(
+            contexts
+                .provided
+                .0
+                .iter()
+                .map(|(type_id, value)| (*type_id, value.clone())),
+        );
+        Arc::new(values)
+    }
+}
+
+impl std::fmt::Debug for SharedContexts {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "SharedContexts(...)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        components::text::{Text, TextProperties},
+        prelude::*,
+        testing::TestBackend,
+    };
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Total(usize);
+
+    struct Provider {
+        total: usize,
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Provider {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self { total: 0, link }
+        }
+
+        fn update(&mut self, _message: Self::Message) -> ShouldRender {
+            self.total += 1;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            self.link.provide(Total(self.total));
+            Layout::column([
+                Item::fixed(1)(Reader::with(())),
+                Item::auto(Text::with(TextProperties::new())),
+            ])
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("increment", [KeyEvent::from(KeyCode::Char('+'))], || ());
+        }
+    }
+
+    /// Shows the total provided when it was created and the current one.
+    struct Reader {
+        created_with: Option<Total>,
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Reader {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self {
+                created_with: link.context::<Total>().map(|total| *total),
+                link,
+            }
+        }
+
+        fn view(&self) -> Layout {
+            let current = self.link.context::<Total>().map(|total| *total);
+            Text::with(
+                TextProperties::new().content(format!("{:?} {:?}", self.created_with, current)),
+            )
+        }
+    }
+
+    #[test]
+    fn provided_values_reach_and_rerender_descendants() {
+        let mut app = TestBackend::new(Size::new(30, 2), Provider::with(()));
+        app.assert_lines(&["Some(Total(0)) Some(Total(0))", ""]);
+
+        // The reader's properties don't change, it's re-rendered because the
+        // provided value did
+        app.keys([KeyCode::Char('+'), KeyCode::Char('+')]);
+        app.assert_lines(&["Some(Total(0)) Some(Total(2))", ""]);
+
+        // Without an ancestor providing it, there is no value
+        let app = TestBackend::new(Size::new(30, 1), Reader::with(()));
+        app.assert_lines(&["None None"]);
+    }
+}
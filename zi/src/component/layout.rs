@@ -33,12 +33,12 @@ pub trait ComponentExt: Component {
     }
 
     fn item_with(flex: FlexBasis, properties: Self::Properties) -> Item {
-        Item {
+        Item::new(
             flex,
-            node: Layout(LayoutNode::Component(DynamicTemplate(Box::new(
+            Layout(LayoutNode::Component(DynamicTemplate(Box::new(
                 ComponentDef::<Self>::new(None, properties),
             )))),
-        }
+        )
     }
 
     fn item_with_key(
@@ -46,12 +46,12 @@ pub trait ComponentExt: Component {
         key: impl Into<ComponentKey>,
         properties: Self::Properties,
     ) -> Item {
-        Item {
+        Item::new(
             flex,
-            node: Layout(LayoutNode::Component(DynamicTemplate(Box::new(
+            Layout(LayoutNode::Component(DynamicTemplate(Box::new(
                 ComponentDef::<Self>::new(Some(key.into()), properties),
             )))),
-        }
+        )
     }
 }
 
@@ -83,6 +83,11 @@ impl From<&str> for ComponentKey {
 ///      [`Container`](./Container).
 ///   3. A canvas which corresponds to the raw content in a region, represented
 ///      by [`Canvas`](./Canvas).
+///   4. A stack of layers drawn on top of each other, represented by
+///      [`Stack`](./Stack).
+///   5. A grid of rows and columns, represented by [`Grid`](./Grid).
+///   6. A scrollable region showing part of a larger `Layout`, represented by
+///      [`Viewport`](./Viewport).
 pub struct Layout(pub(crate) LayoutNode);
 
 impl Layout {
@@ -147,19 +152,44 @@ impl Layout {
     pub fn row_reverse(items: impl IntoIterator<Item = Item>) -> Self {
         Container::row_reverse(items).into()
     }
+
+    /// Creates a stack of layers drawn on top of each other.
+    ///
+    /// This is a utility function that builds a stack and converts it to a `Layout`.
+    /// It is equivalent to calling `Stack::new(layers).into()`.
+    #[inline]
+    pub fn stack(layers: impl IntoIterator<Item = Layer>) -> Self {
+        Stack::new(layers).into()
+    }
 }
 
 pub(crate) enum LayoutNode {
     Container(Box<Container>),
+    Grid(Box<Grid>),
+    Stack(Box<Stack>),
+    Viewport(Box<Viewport>),
     Component(DynamicTemplate),
     Canvas(Canvas),
 }
 
 impl LayoutNode {
+    /// Lays out the tree in `frame`, calling `view_fn` for each component and
+    /// `draw_fn` for each canvas in drawing order.
+    ///
+    /// `level` is the overlay level of the node, i.e. the number of overlays
+    /// it is nested in. Layers above the lowest one of a `Stack` are one level
+    /// higher than the stack itself.
+    ///
+    /// `clip` is the visible part of the layout space `frame` is in. It is
+    /// only narrowed down by viewports, so everything else may be laid out
+    /// outside of its parent's frame as before.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn crawl(
         &mut self,
         frame: Rect,
         position_hash: u64,
+        level: usize,
+        clip: Clip,
         view_fn: &mut impl FnMut(LaidComponent),
         draw_fn: &mut impl FnMut(LaidCanvas),
     ) {
@@ -170,22 +200,121 @@ impl LayoutNode {
                 hasher.write_u64(Self::CONTAINER_HASH);
                 if container.direction.is_reversed() {
                     let frames: SmallVec<[_; ITEMS_INLINE_SIZE]> =
-                        splits_iter(frame, container.direction, container.children.iter().rev())
-                            .collect();
-                    for (child, frame) in container.children.iter_mut().rev().zip(frames) {
-                        // hasher.write_u64(Self::CONTAINER_ITEM_HASH);
-                        child.node.0.crawl(frame, hasher.finish(), view_fn, draw_fn);
+                        splits_iter(frame, container, container.children.iter().rev()).collect();
+                    for ((index, child), frame) in
+                        container.children.iter_mut().enumerate().rev().zip(frames)
+                    {
+                        let item_hash = child.node.0.item_hash(&hasher, index);
+                        child
+                            .node
+                            .0
+                            .crawl(frame, item_hash, level, clip, view_fn, draw_fn);
                     }
                 } else {
                     let frames: SmallVec<[_; ITEMS_INLINE_SIZE]> =
-                        splits_iter(frame, container.direction, container.children.iter())
-                            .collect();
-                    for (child, frame) in container.children.iter_mut().zip(frames) {
-                        // hasher.write_u64(Self::CONTAINER_ITEM_HASH);
-                        child.node.0.crawl(frame, hasher.finish(), view_fn, draw_fn);
+                        splits_iter(frame, container, container.children.iter()).collect();
+                    for ((index, child), frame) in
+                        container.children.iter_mut().enumerate().zip(frames)
+                    {
+                        let item_hash = child.node.0.item_hash(&hasher, index);
+                        child
+                            .node
+                            .0
+                            .crawl(frame, item_hash, level, clip, view_fn, draw_fn);
                     }
                 }
             }
+            Self::Grid(grid) => {
+                hasher.write_u64(Self::GRID_HASH);
+                let columns = track_offsets(&grid.columns, frame.size.width, grid.column_gap);
+                let rows = track_offsets(&grid.rows, frame.size.height, grid.row_gap);
+                for (index, cell) in grid.cells.iter_mut().enumerate() {
+                    let (x, width) = cell_span(&columns, cell.column, cell.column_span);
+                    let (y, height) = cell_span(&rows, cell.row, cell.row_span);
+                    let cell_frame = Rect::new(
+                        Position::new(frame.origin.x + x, frame.origin.y + y),
+                        Size::new(
+                            cmp::min(width, frame.size.width.saturating_sub(x)),
+                            cmp::min(height, frame.size.height.saturating_sub(y)),
+                        ),
+                    );
+                    let item_hash = cell.node.0.item_hash(&hasher, index);
+                    cell.node
+                        .0
+                        .crawl(cell_frame, item_hash, level, clip, view_fn, draw_fn);
+                }
+            }
+            Self::Stack(stack) => {
+                hasher.write_u64(Self::STACK_HASH);
+                // Layers are drawn from the lowest to the highest z-index,
+                // keeping their order in the stack for equal z-indices
+                let mut order: SmallVec<[_; LAYERS_INLINE_SIZE]> =
+                    (0..stack.layers.len()).collect();
+                order.sort_by_key(|&index| stack.layers[index].z_index);
+                for (position, index) in order.into_iter().enumerate() {
+                    let layer = &mut stack.layers[index];
+                    let layer_frame = match layer.placement {
+                        Placement::Fill => frame,
+                        Placement::Relative(relative) => Rect::new(
+                            Position::new(
+                                frame.origin.x + relative.origin.x,
+                                frame.origin.y + relative.origin.y,
+                            ),
+                            relative.size,
+                        ),
+                        // Screen positions are shifted into the layout space
+                        // of an enclosing viewport
+                        Placement::Absolute(absolute) => Rect::new(
+                            Position::new(
+                                absolute.origin.x + clip.offset.x,
+                                absolute.origin.y + clip.offset.y,
+                            ),
+                            absolute.size,
+                        ),
+                    };
+                    let layer_level = if position == 0 { level } else { level + 1 };
+                    let item_hash = layer.node.0.item_hash(&hasher, index);
+                    layer
+                        .node
+                        .0
+                        .crawl(layer_frame, item_hash, layer_level, clip, view_fn, draw_fn);
+                }
+            }
+            Self::Viewport(viewport) => {
+                hasher.write_u64(Self::VIEWPORT_HASH);
+                // The content covers at least the viewport's frame and the
+                // offset is clamped to keep it that way
+                let content_size = Size::new(
+                    cmp::max(viewport.size.width, frame.size.width),
+                    cmp::max(viewport.size.height, frame.size.height),
+                );
+                let offset = Position::new(
+                    cmp::min(viewport.offset.x, content_size.width - frame.size.width),
+                    cmp::min(viewport.offset.y, content_size.height - frame.size.height),
+                );
+
+                // The content is laid out from the viewport's origin, in a
+                // layout space shifted by the offset relative to the parent's
+                let shift = |rect: Rect| {
+                    Rect::new(
+                        Position::new(rect.origin.x + offset.x, rect.origin.y + offset.y),
+                        rect.size,
+                    )
+                };
+                let content_clip = Clip {
+                    region: intersection(shift(clip.region), shift(frame)),
+                    offset: Position::new(clip.offset.x + offset.x, clip.offset.y + offset.y),
+                };
+                let item_hash = viewport.content.0.item_hash(&hasher, 0);
+                viewport.content.0.crawl(
+                    Rect::new(frame.origin, content_size),
+                    item_hash,
+                    level,
+                    content_clip,
+                    view_fn,
+                    draw_fn,
+                );
+            }
             Self::Component(template) => {
                 template.component_type_id().hash(&mut hasher);
                 if let Some(key) = template.key() {
@@ -194,19 +323,56 @@ impl LayoutNode {
                 view_fn(LaidComponent {
                     frame,
                     position_hash: hasher.finish(),
+                    level,
+                    clip,
                     template,
                 });
             }
             Self::Canvas(canvas) => {
-                draw_fn(LaidCanvas { frame, canvas });
+                draw_fn(LaidCanvas {
+                    frame,
+                    level,
+                    clip,
+                    canvas,
+                });
             }
         };
     }
 
+    /// Computes the position hash of a container's child. Keyed components are
+    /// identified by their key alone, so they keep their identity when
+    /// reordered. Everything else is identified by its index in the container,
+    /// which tells apart unkeyed siblings of the same type.
+    fn item_hash(&self, container_hasher: &DefaultHasher, index: usize) -> u64 {
+        let mut hasher = container_hasher.clone();
+        match self {
+            Self::Component(template) if template.key().is_some() => {}
+            _ => {
+                hasher.write_u64(Self::CONTAINER_ITEM_HASH);
+                hasher.write_usize(index);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Returns the preferred size of the node, see `Component::size_hint`.
+    /// Canvases prefer their own size, containers have no preference.
+    fn size_hint(&self, available: Size) -> Option<Size> {
+        match self {
+            Self::Component(template) => template.size_hint(available),
+            Self::Canvas(canvas) => Some(canvas.size()),
+            Self::Container(_) | Self::Grid(_) | Self::Stack(_) | Self::Viewport(_) => None,
+        }
+    }
+
     // Some random number to initialise the hash (0 would also do, but hopefully
     // this is less pathological if a simpler hash function is used for
     // `DefaultHasher`).
     const CONTAINER_HASH: u64 = 0x5aa2d5349a05cde8;
+    const CONTAINER_ITEM_HASH: u64 = 0x8f3a0c17d2b6e491;
+    const STACK_HASH: u64 = 0x2c61e8b90f4d7a35;
+    const GRID_HASH: u64 = 0x71d4a9e3c05b28f6;
+    const VIEWPORT_HASH: u64 = 0xe6093b7fa41c52d8;
 }
 
 impl From<Canvas> for Layout {
@@ -219,9 +385,16 @@ const ITEMS_INLINE_SIZE: usize = 4;
 type Items = SmallVec<[Item; ITEMS_INLINE_SIZE]>;
 
 /// A flex container with a specified direction and items.
+///
+/// Space left over after sizing the items is distributed along the main axis
+/// according to the container's [`Justify`](./Justify) setting. On the cross
+/// axis, items are stretched to fill the container unless they have a cross
+/// size, in which case they are positioned according to [`Align`](./Align).
 pub struct Container {
     children: Items,
     direction: FlexDirection,
+    justify: Justify,
+    align: Align,
 }
 
 impl Container {
@@ -248,6 +421,8 @@ impl Container {
         Self {
             children: items.into_iter().collect(),
             direction,
+            justify: Justify::Start,
+            align: Align::Stretch,
         }
     }
 
@@ -257,9 +432,42 @@ impl Container {
         Self {
             children: SmallVec::new(),
             direction,
+            justify: Justify::Start,
+            align: Align::Stretch,
         }
     }
 
+    /// Sets how space left over along the main axis is distributed around
+    /// the items. The default is `Justify::Start`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// # fn main() {
+    /// let text = |content| Text::with(TextProperties::new().content(content));
+    /// let buttons = Container::row([
+    ///     Item::fixed(6)(text("[ OK ]")).margin(Edges::symmetric(0, 1)),
+    ///     Item::fixed(10)(text("[ Cancel ]")).margin(Edges::symmetric(0, 1)),
+    /// ])
+    /// .justify(Justify::Center);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn justify(mut self, justify: Justify) -> Self {
+        self.justify = justify;
+        self
+    }
+
+    /// Sets the default cross axis alignment of the items. The default is
+    /// `Align::Stretch`.
+    #[inline]
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
     /// Adds an item to the end of the container.
     ///
     /// # Example
@@ -330,14 +538,399 @@ impl From<Container> for Layout {
     }
 }
 
+/// A grid container with rows and columns of tracks.
+///
+/// Each cell of the grid is placed at a row and column and may span several
+/// tracks. Unlike nested rows and columns, the widths of the columns are
+/// the same in every row, which makes grids useful for forms and key/value
+/// panels.
+///
+/// # Example
+///
+/// ```rust
+/// # use zi::prelude::*;
+/// # use zi::components::text::{Text, TextProperties};
+/// # fn main() {
+/// let text = |content| Text::with(TextProperties::new().content(content));
+/// let form = Grid::new([Track::Fixed(10), Track::Fraction(1)], [Track::Fixed(1); 3])
+///     .column_gap(1)
+///     .cells([
+///         GridCell::new(0, 0, text("Name")),
+///         GridCell::new(0, 1, text("Zi")),
+///         GridCell::new(1, 0, text("License")),
+///         GridCell::new(1, 1, text("MIT OR Apache-2.0")),
+///         GridCell::new(2, 0, text("A description spanning both columns")).span(1, 2),
+///     ]);
+/// # }
+/// ```
+pub struct Grid {
+    columns: SmallVec<[Track; ITEMS_INLINE_SIZE]>,
+    rows: SmallVec<[Track; ITEMS_INLINE_SIZE]>,
+    column_gap: usize,
+    row_gap: usize,
+    cells: SmallVec<[GridCell; ITEMS_INLINE_SIZE]>,
+}
+
+impl Grid {
+    /// Creates an empty grid with the given column and row tracks.
+    #[inline]
+    pub fn new(
+        columns: impl IntoIterator<Item = Track>,
+        rows: impl IntoIterator<Item = Track>,
+    ) -> Self {
+        Self {
+            columns: columns.into_iter().collect(),
+            rows: rows.into_iter().collect(),
+            column_gap: 0,
+            row_gap: 0,
+            cells: SmallVec::new(),
+        }
+    }
+
+    /// Sets the number of blank columns between column tracks.
+    #[inline]
+    pub fn column_gap(mut self, gap: usize) -> Self {
+        self.column_gap = gap;
+        self
+    }
+
+    /// Sets the number of blank rows between row tracks.
+    #[inline]
+    pub fn row_gap(mut self, gap: usize) -> Self {
+        self.row_gap = gap;
+        self
+    }
+
+    /// Adds cells to the grid.
+    #[inline]
+    pub fn cells(mut self, cells: impl IntoIterator<Item = GridCell>) -> Self {
+        self.cells.extend(cells);
+        self
+    }
+
+    /// Adds a cell to the grid.
+    #[inline]
+    pub fn push(&mut self, cell: GridCell) -> &mut Self {
+        self.cells.push(cell);
+        self
+    }
+}
+
+impl From<Grid> for Layout {
+    fn from(grid: Grid) -> Self {
+        Layout(LayoutNode::Grid(Box::new(grid)))
+    }
+}
+
+/// The size of a row or column of a [`Grid`](struct.Grid.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Track {
+    /// A fixed size.
+    Fixed(usize),
+    /// An equal share of the space left after fixed tracks, the same as
+    /// `Fraction(1)`.
+    Auto,
+    /// A share of the space left after fixed tracks, in proportion to the
+    /// fractions of the other tracks.
+    Fraction(usize),
+}
+
+/// A cell of a [`Grid`](struct.Grid.html), a layout tree placed at a row and
+/// column.
+pub struct GridCell {
+    node: Layout,
+    row: usize,
+    column: usize,
+    row_span: usize,
+    column_span: usize,
+}
+
+impl GridCell {
+    /// Creates a cell at a row and column, both starting at 0.
+    #[inline]
+    pub fn new(row: usize, column: usize, layout: impl Into<Layout>) -> Self {
+        Self {
+            node: layout.into(),
+            row,
+            column,
+            row_span: 1,
+            column_span: 1,
+        }
+    }
+
+    /// Makes the cell span multiple rows and columns. Spans beyond the last
+    /// track of the grid are cut off.
+    #[inline]
+    pub fn span(mut self, rows: usize, columns: usize) -> Self {
+        self.row_span = rows;
+        self.column_span = columns;
+        self
+    }
+}
+
+/// Computes the offset and size of each track.
+fn track_offsets(tracks: &[Track], total_size: usize, gap: usize) -> Vec<(usize, usize)> {
+    let total_gaps = gap * tracks.len().saturating_sub(1);
+    let available_size = total_size.saturating_sub(total_gaps);
+    let mut sizes: Sizes = tracks
+        .iter()
+        .map(|track| match *track {
+            Track::Fixed(size) => size,
+            Track::Auto | Track::Fraction(_) => 0,
+        })
+        .collect();
+    let weights: Sizes = tracks
+        .iter()
+        .map(|track| match *track {
+            Track::Fixed(_) => 0,
+            Track::Auto => 1,
+            Track::Fraction(weight) => weight,
+        })
+        .collect();
+    let limits: Sizes = tracks.iter().map(|_| usize::MAX).collect();
+    let used_size: usize = sizes.iter().sum();
+    flex(
+        &mut sizes,
+        &weights,
+        &limits,
+        available_size.saturating_sub(used_size),
+        true,
+    );
+
+    let mut offset = 0;
+    sizes
+        .into_iter()
+        .map(|size| {
+            let track = (offset, size);
+            offset += size + gap;
+            track
+        })
+        .collect()
+}
+
+/// Returns the offset and size of a cell spanning `span` tracks from `start`,
+/// including the gaps between them.
+fn cell_span(tracks: &[(usize, usize)], start: usize, span: usize) -> (usize, usize) {
+    let end = cmp::min(start.saturating_add(span), tracks.len());
+    match (
+        tracks.get(start),
+        end.checked_sub(1).and_then(|last| tracks.get(last)),
+    ) {
+        (Some(&(offset, _)), Some(&(last_offset, last_size))) if start < end => {
+            (offset, last_offset + last_size - offset)
+        }
+        _ => (0, 0),
+    }
+}
+
+/// A scrollable region showing part of a larger layout.
+///
+/// The content is laid out at the viewport's content size, or at the size of
+/// the viewport's frame if that is larger, and clipped to the frame. The
+/// offset is the position of the content that is shown at the top left of the
+/// frame and is clamped so that the content always covers the frame.
+///
+/// Components inside the viewport are created and drawn at their full size,
+/// even when only part of them is visible. See the
+/// [`ScrollView`](../components/scroll_view/struct.ScrollView.html) component
+/// for a viewport with keyboard scrolling and a scrollbar.
+///
+/// # Example
+///
+/// ```rust
+/// # use zi::prelude::*;
+/// # use zi::components::text::{Text, TextProperties};
+/// # fn main() {
+/// let lines: String = (0..100).map(|line| format!("Line {}\n", line)).collect();
+/// let viewport = Viewport::new(
+///     Size::new(20, 100),
+///     Text::with(TextProperties::new().content(lines)),
+/// )
+/// .offset(Position::new(0, 50));
+/// # }
+/// ```
+pub struct Viewport {
+    content: Layout,
+    size: Size,
+    offset: Position,
+}
+
+impl Viewport {
+    /// Creates a viewport for content of the given size, scrolled to the top
+    /// left.
+    #[inline]
+    pub fn new(size: Size, content: impl Into<Layout>) -> Self {
+        Self {
+            content: content.into(),
+            size,
+            offset: Position::new(0, 0),
+        }
+    }
+
+    /// Sets the position of the content shown at the top left of the frame.
+    #[inline]
+    pub fn offset(mut self, offset: Position) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+impl From<Viewport> for Layout {
+    fn from(viewport: Viewport) -> Self {
+        Self(LayoutNode::Viewport(Box::new(viewport)))
+    }
+}
+
+const LAYERS_INLINE_SIZE: usize = 2;
+
+/// A stack of layers drawn on top of each other, e.g. a popup or a modal
+/// dialog over the rest of the UI.
+///
+/// Layers are drawn in order of their z-index, then in the order they were
+/// added. Every layer above the lowest one is an overlay: it is drawn after
+/// everything that is not an overlay, even outside of the stack's frame, and
+/// takes precedence for input. Keys go to the focused components in the
+/// topmost overlay, and mouse events to the topmost overlay under the cursor.
+///
+/// # Example
+///
+/// ```rust
+/// # use zi::prelude::*;
+/// # use zi::components::text::{Text, TextProperties};
+/// # fn main() {
+/// let dialog = Layout::stack([
+///     Layer::new(Text::with(TextProperties::new().content("Editor"))),
+///     Layer::new(Text::with(TextProperties::new().content("Save changes?")))
+///         .at(Rect::new(Position::new(10, 5), Size::new(20, 3))),
+/// ]);
+/// # }
+/// ```
+pub struct Stack {
+    layers: SmallVec<[Layer; LAYERS_INLINE_SIZE]>,
+}
+
+impl Stack {
+    /// Creates a new stack containing the provided layers.
+    #[inline]
+    pub fn new(layers: impl IntoIterator<Item = Layer>) -> Self {
+        Self {
+            layers: layers.into_iter().collect(),
+        }
+    }
+
+    /// Creates a new empty stack.
+    #[inline]
+    pub fn empty() -> Self {
+        Self {
+            layers: SmallVec::new(),
+        }
+    }
+
+    /// Adds a layer on top of the existing layers with the same z-index.
+    #[inline]
+    pub fn push(&mut self, layer: Layer) -> &mut Self {
+        self.layers.push(layer);
+        self
+    }
+}
+
+impl From<Stack> for Layout {
+    fn from(stack: Stack) -> Self {
+        Layout(LayoutNode::Stack(Box::new(stack)))
+    }
+}
+
+/// A layer of a [`Stack`](struct.Stack.html), a layout tree with a position
+/// and a z-index.
+pub struct Layer {
+    node: Layout,
+    placement: Placement,
+    z_index: i32,
+}
+
+impl Layer {
+    /// Creates a layer which fills the frame of the stack.
+    #[inline]
+    pub fn new(layout: impl Into<Layout>) -> Self {
+        Self {
+            node: layout.into(),
+            placement: Placement::Fill,
+            z_index: 0,
+        }
+    }
+
+    /// Places the layer at a frame relative to the origin of the stack. The
+    /// layer may extend beyond the stack's frame.
+    #[inline]
+    pub fn at(mut self, frame: Rect) -> Self {
+        self.placement = Placement::Relative(frame);
+        self
+    }
+
+    /// Places the layer at a frame relative to the screen.
+    #[inline]
+    pub fn absolute(mut self, frame: Rect) -> Self {
+        self.placement = Placement::Absolute(frame);
+        self
+    }
+
+    /// Sets the z-index of the layer. Layers with a higher z-index are drawn
+    /// on top of layers with a lower one. The default is 0.
+    #[inline]
+    pub fn z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+}
+
+/// Where a [`Layer`](struct.Layer.html) is placed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Placement {
+    /// Fills the frame of the stack.
+    Fill,
+    /// At a frame relative to the origin of the stack.
+    Relative(Rect),
+    /// At a frame relative to the screen.
+    Absolute(Rect),
+}
+
 /// Represents a flex item, a layout tree nested inside a container.
 ///
 /// An `Item` consists of a `Layout` and an associated `FlexBasis`. The latter
 /// specifies how much space the layout should take along the main axis of the
-/// container.
+/// container. Similar to CSS flexbox, the remaining space is then shared
+/// between items in proportion to their grow factors, and if the items don't
+/// fit they shrink in proportion to their shrink factors. Sizes never go below
+/// an item's minimum or above its maximum size.
+///
+/// # Example
+///
+/// A sidebar taking 30% of the width, but never less than 20 columns, next to
+/// two panes splitting the rest 2:1.
+///
+/// ```rust
+/// # use zi::prelude::*;
+/// # use zi::components::text::{Text, TextProperties};
+/// # fn main() {
+/// let text = |content| Text::with(TextProperties::new().content(content));
+/// let layout = Layout::row([
+///     Item::percent(30)(text("Sidebar")).min_size(20),
+///     Item::auto(text("Editor")).grow(2),
+///     Item::auto(text("Preview")),
+/// ]);
+/// # }
+/// ```
 pub struct Item {
     node: Layout,
     flex: FlexBasis,
+    grow: usize,
+    shrink: usize,
+    min_size: usize,
+    max_size: Option<usize>,
+    cross_size: Option<usize>,
+    align: Option<Align>,
+    margin: Edges,
+    padding: Edges,
 }
 
 impl Item {
@@ -345,10 +938,7 @@ impl Item {
     /// sibling items with `FlexBasis::auto`.
     #[inline]
     pub fn auto(layout: impl Into<Layout>) -> Item {
-        Item {
-            node: layout.into(),
-            flex: FlexBasis::Auto,
-        }
+        Item::new(FlexBasis::Auto, layout.into())
     }
 
     /// Creates an item that will have a fixed size.
@@ -357,9 +947,170 @@ impl Item {
     where
         LayoutT: Into<Layout>,
     {
-        move |layout| Item {
-            node: layout.into(),
-            flex: FlexBasis::Fixed(size),
+        move |layout| Item::new(FlexBasis::Fixed(size), layout.into())
+    }
+
+    /// Creates an item sized to its content, as reported by
+    /// `Component::size_hint`. Content items don't grow by default.
+    ///
+    /// # Example
+    ///
+    /// A status line as tall as its text, with the rest of the space left for
+    /// the editor.
+    ///
+    /// ```rust
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// # fn main() {
+    /// let text = |content| Text::with(TextProperties::new().content(content));
+    /// let layout = Layout::column([
+    ///     Item::auto(text("Editor")),
+    ///     Item::content(text("NORMAL  main.rs\n:w")),
+    /// ]);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn content(layout: impl Into<Layout>) -> Item {
+        Item::new(FlexBasis::Content, layout.into())
+    }
+
+    /// Creates an item with a size that is a percentage of the container's
+    /// size.
+    #[inline]
+    pub fn percent<LayoutT>(percent: usize) -> impl FnOnce(LayoutT) -> Item
+    where
+        LayoutT: Into<Layout>,
+    {
+        move |layout| Item::new(FlexBasis::Percent(percent), layout.into())
+    }
+
+    /// Sets the grow factor, i.e. the item's share of any space left in the
+    /// container after all items got their basis size. Items with
+    /// `FlexBasis::Auto` have a grow factor of 1 by default, others 0.
+    #[inline]
+    pub fn grow(mut self, grow: usize) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    /// Sets the shrink factor. If the items don't fit the container, they
+    /// shrink in proportion to their shrink factor multiplied by their basis
+    /// size. The default is 0, which means items keep their size and the ones
+    /// at the end are cut off.
+    #[inline]
+    pub fn shrink(mut self, shrink: usize) -> Self {
+        self.shrink = shrink;
+        self
+    }
+
+    /// Sets the minimum size of the item.
+    #[inline]
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Sets the maximum size of the item.
+    #[inline]
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Sets the size of the item along the cross axis of the container, e.g.
+    /// its height in a row. By default items fill the container's cross axis.
+    #[inline]
+    pub fn cross_size(mut self, cross_size: usize) -> Self {
+        self.cross_size = Some(cross_size);
+        self
+    }
+
+    /// Overrides the container's cross axis alignment for this item.
+    #[inline]
+    pub fn align_self(mut self, align: Align) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    /// Sets the space around the item. Margins are added to the item's size
+    /// and separate it from its siblings and the container's edges.
+    #[inline]
+    pub fn margin(mut self, margin: impl Into<Edges>) -> Self {
+        self.margin = margin.into();
+        self
+    }
+
+    /// Sets the space between the edges of the item and its content. Padding
+    /// is taken out of the item's size.
+    #[inline]
+    pub fn padding(mut self, padding: impl Into<Edges>) -> Self {
+        self.padding = padding.into();
+        self
+    }
+
+    #[inline]
+    fn new(flex: FlexBasis, node: Layout) -> Self {
+        Self {
+            node,
+            flex,
+            grow: if flex == FlexBasis::Auto { 1 } else { 0 },
+            shrink: 0,
+            min_size: 0,
+            max_size: None,
+            cross_size: None,
+            align: None,
+            margin: Edges::default(),
+            padding: Edges::default(),
+        }
+    }
+
+    /// The size of the item before growing or shrinking.
+    #[inline]
+    fn basis_size(&self, container_size: usize, content_size: Option<usize>) -> usize {
+        let size = match self.flex {
+            FlexBasis::Auto => 0,
+            FlexBasis::Fixed(size) => size,
+            FlexBasis::Percent(percent) => container_size * percent / 100,
+            FlexBasis::Content => content_size.unwrap_or(0),
+        };
+        self.clamp(size)
+    }
+
+    /// The preferred size of the item's content plus its padding, given the
+    /// size of the container. Only computed for `FlexBasis::Content` items.
+    #[inline]
+    fn content_size(&self, container_size: Size) -> Option<Size> {
+        if self.flex != FlexBasis::Content {
+            return None;
+        }
+        let Edges {
+            top,
+            right,
+            bottom,
+            left,
+        } = self.margin;
+        let horizontal = self.padding.left + self.padding.right;
+        let vertical = self.padding.top + self.padding.bottom;
+        let available = Size::new(
+            container_size
+                .width
+                .saturating_sub(left + right + horizontal),
+            container_size
+                .height
+                .saturating_sub(top + bottom + vertical),
+        );
+        self.node
+            .0
+            .size_hint(available)
+            .map(|size| Size::new(size.width + horizontal, size.height + vertical))
+    }
+
+    #[inline]
+    fn clamp(&self, size: usize) -> usize {
+        let size = cmp::max(size, self.min_size);
+        match self.max_size {
+            Some(max_size) => cmp::min(size, cmp::max(max_size, self.min_size)),
+            None => size,
         }
     }
 }
@@ -369,6 +1120,11 @@ impl Item {
 pub enum FlexBasis {
     Auto,
     Fixed(usize),
+    /// A percentage of the container's size along its main axis.
+    Percent(usize),
+    /// The size hint of the item's component. Items whose component has no
+    /// size hint grow like `Auto` items.
+    Content,
 }
 
 /// Enum to control how items are placed in a container. It defines the main
@@ -399,84 +1155,590 @@ impl FlexDirection {
             FlexDirection::ColumnReverse => size.height,
         }
     }
+
+    #[inline]
+    fn is_row(self) -> bool {
+        match self {
+            FlexDirection::Row | FlexDirection::RowReverse => true,
+            FlexDirection::Column | FlexDirection::ColumnReverse => false,
+        }
+    }
+}
+
+/// Enum to control how space left over along the main axis of a container is
+/// distributed around its items.
+///
+/// Positions are on screen, i.e. `Start` is the left or top edge of the
+/// container, also for reversed directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Justify {
+    /// Items are packed at the start of the container.
+    Start,
+    /// Items are packed in the middle of the container.
+    Center,
+    /// Items are packed at the end of the container.
+    End,
+    /// The first and last items are at the edges of the container, with
+    /// equal space between items.
+    SpaceBetween,
+    /// Each item has equal space on both sides, so the space between items is
+    /// twice the space at the edges.
+    SpaceAround,
+}
+
+impl Justify {
+    /// Returns the amount of free space before the item at `index`, out of
+    /// `count` items.
+    #[inline]
+    fn space_before(self, index: usize, count: usize, free_size: usize) -> usize {
+        match self {
+            Justify::Start => 0,
+            Justify::Center => free_size / 2,
+            Justify::End => free_size,
+            Justify::SpaceBetween if count > 1 => free_size * index / (count - 1),
+            Justify::SpaceBetween => 0,
+            Justify::SpaceAround => free_size * (2 * index + 1) / (2 * count),
+        }
+    }
+}
+
+/// Enum to control how items are positioned on the cross axis of a container.
+///
+/// Positions are on screen, i.e. `Start` is the left or top edge of the
+/// container, also for reversed directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Align {
+    /// Items fill the container, unless they have a cross size in which case
+    /// they are placed at the start.
+    Stretch,
+    Start,
+    Center,
+    End,
+}
+
+impl Align {
+    /// Returns the offset and size of an item on the cross axis.
+    #[inline]
+    fn place(self, cross_size: Option<usize>, available_size: usize) -> (usize, usize) {
+        let size = cross_size.map_or(available_size, |size| cmp::min(size, available_size));
+        let free_size = available_size - size;
+        let offset = match self {
+            Align::Stretch | Align::Start => 0,
+            Align::Center => free_size / 2,
+            Align::End => free_size,
+        };
+        (offset, size)
+    }
+}
+
+/// Sizes of space on each side of a rectangle, used for the margin and
+/// padding of items.
+///
+/// A single `usize` converts to the same size on all sides.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Edges {
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize,
+    pub left: usize,
+}
+
+impl Edges {
+    /// Creates edges with the given sizes, clockwise from the top like in CSS.
+    #[inline]
+    pub const fn new(top: usize, right: usize, bottom: usize, left: usize) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Creates edges with the same size on all sides.
+    #[inline]
+    pub const fn all(size: usize) -> Self {
+        Self::new(size, size, size, size)
+    }
+
+    /// Creates edges with one size for the top and bottom and another for the
+    /// left and right.
+    #[inline]
+    pub const fn symmetric(vertical: usize, horizontal: usize) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    /// Returns the sizes of the leading and trailing edges along an axis.
+    #[inline]
+    fn along(self, row: bool) -> (usize, usize) {
+        if row {
+            (self.left, self.right)
+        } else {
+            (self.top, self.bottom)
+        }
+    }
+
+    /// Shrinks a rectangle by the edges.
+    #[inline]
+    fn inset(self, rect: Rect) -> Rect {
+        let x = cmp::min(self.left, rect.size.width);
+        let y = cmp::min(self.top, rect.size.height);
+        Rect::new(
+            Position::new(rect.origin.x + x, rect.origin.y + y),
+            Size::new(
+                rect.size.width.saturating_sub(self.left + self.right),
+                rect.size.height.saturating_sub(self.top + self.bottom),
+            ),
+        )
+    }
+}
+
+impl From<usize> for Edges {
+    fn from(size: usize) -> Self {
+        Self::all(size)
+    }
 }
 
 pub(crate) struct LaidComponent<'a> {
     pub frame: Rect,
     pub position_hash: u64,
+    pub level: usize,
+    pub clip: Clip,
     pub template: &'a mut DynamicTemplate,
 }
 
 pub(crate) struct LaidCanvas<'a> {
     pub frame: Rect,
+    pub level: usize,
+    pub clip: Clip,
     pub canvas: &'a Canvas,
 }
 
+/// The visible region of a layout space and where it is on the screen.
+///
+/// Frames are given in layout space, which is the same as screen space except
+/// inside a `Viewport`. There, content is laid out in a space shifted by the
+/// viewport's offset, so that frames never have negative coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Clip {
+    /// The visible region, in layout space.
+    pub region: Rect,
+    /// The position in layout space of the screen's origin.
+    pub offset: Position,
+}
+
+impl Clip {
+    /// Creates a clip for a region of the screen.
+    #[inline]
+    pub(crate) fn new(region: Rect) -> Self {
+        Self {
+            region,
+            offset: Position::new(0, 0),
+        }
+    }
+
+    /// Returns the visible part of a frame, in screen space.
+    #[inline]
+    pub(crate) fn visible(&self, frame: Rect) -> Rect {
+        let visible = intersection(frame, self.region);
+        Rect::new(
+            Position::new(
+                visible.origin.x.saturating_sub(self.offset.x),
+                visible.origin.y.saturating_sub(self.offset.y),
+            ),
+            visible.size,
+        )
+    }
+
+    /// Converts a position on the screen to layout space.
+    #[inline]
+    pub(crate) fn screen_to_layout(&self, position: Position) -> Position {
+        Position::new(position.x + self.offset.x, position.y + self.offset.y)
+    }
+}
+
+/// Returns the overlap of two rectangles, which is empty if they are
+/// disjoint.
+fn intersection(first: Rect, second: Rect) -> Rect {
+    let origin = Position::new(
+        cmp::max(first.min_x(), second.min_x()),
+        cmp::max(first.min_y(), second.min_y()),
+    );
+    Rect::new(
+        origin,
+        Size::new(
+            cmp::min(first.max_x(), second.max_x()).saturating_sub(origin.x),
+            cmp::min(first.max_y(), second.max_y()).saturating_sub(origin.y),
+        ),
+    )
+}
+
 #[inline]
 fn splits_iter<'a>(
     frame: Rect,
-    direction: FlexDirection,
+    container: &Container,
     children: impl Iterator<Item = &'a Item> + Clone + 'a,
 ) -> impl Iterator<Item = Rect> + 'a {
+    let Container {
+        direction,
+        justify,
+        align,
+        ..
+    } = *container;
+    let row = direction.is_row();
     let total_size = direction.dimension(frame.size);
+    let cross_total_size = if row {
+        frame.size.height
+    } else {
+        frame.size.width
+    };
 
-    // Compute how much space is available for stretched components
-    let (stretched_budget, num_stretched_children, total_fixed_size) = {
-        let mut stretched_budget = total_size;
-        let mut num_stretched_children = 0;
-        let mut total_fixed_size = 0;
-        for child in children.clone() {
-            match child.flex {
-                FlexBasis::Auto => {
-                    num_stretched_children += 1;
-                }
-                FlexBasis::Fixed(size) => {
-                    stretched_budget = stretched_budget.saturating_sub(size);
-                    total_fixed_size += size;
-                }
-            }
+    let margins_size: usize = children
+        .clone()
+        .map(|child| {
+            let (start, end) = child.margin.along(row);
+            start + end
+        })
+        .sum();
+    let content_sizes: SmallVec<[_; ITEMS_INLINE_SIZE]> = children
+        .clone()
+        .map(|child| child.content_size(frame.size))
+        .collect();
+    let main_content_sizes: SmallVec<[_; ITEMS_INLINE_SIZE]> = content_sizes
+        .iter()
+        .map(|size| size.map(|size| if row { size.width } else { size.height }))
+        .collect();
+    let sizes = flex_sizes(
+        total_size.saturating_sub(margins_size),
+        children.clone(),
+        &main_content_sizes,
+    );
+    let free_size = total_size.saturating_sub(margins_size + sizes.iter().sum::<usize>());
+    let count = sizes.len();
+
+    let mut used_size = 0;
+    children.zip(sizes).zip(content_sizes).enumerate().map(
+        move |(index, ((child, size), content_size))| {
+            let (margin_start, margin_end) = child.margin.along(row);
+            let offset = used_size + justify.space_before(index, count, free_size) + margin_start;
+            used_size += margin_start + size + margin_end;
+
+            // Items that don't fit are cut off at the end of the container
+            let offset = cmp::min(offset, total_size);
+            let size = cmp::min(size, total_size - offset);
+
+            let (margin_start, margin_end) = child.margin.along(!row);
+            let available_size = cross_total_size.saturating_sub(margin_start + margin_end);
+            // Unless stretched, content items also take their preferred size
+            // on the cross axis
+            let align = child.align.unwrap_or(align);
+            let cross_size = child.cross_size.or_else(|| match align {
+                Align::Stretch => None,
+                _ => content_size.map(|size| if row { size.height } else { size.width }),
+            });
+            let (cross_offset, cross_size) = align.place(cross_size, available_size);
+            let cross_offset = cmp::min(margin_start + cross_offset, cross_total_size);
+
+            let (x, y, width, height) = if row {
+                (offset, cross_offset, size, cross_size)
+            } else {
+                (cross_offset, offset, cross_size, size)
+            };
+            child.padding.inset(Rect::new(
+                Position::new(frame.origin.x + x, frame.origin.y + y),
+                Size::new(width, height),
+            ))
+        },
+    )
+}
+
+type Sizes = SmallVec<[usize; ITEMS_INLINE_SIZE]>;
+
+/// Computes the sizes of items along the main axis of a container.
+fn flex_sizes<'a>(
+    total_size: usize,
+    children: impl Iterator<Item = &'a Item> + Clone,
+    content_sizes: &[Option<usize>],
+) -> Sizes {
+    let mut sizes: Sizes = children
+        .clone()
+        .zip(content_sizes)
+        .map(|(child, &content_size)| child.basis_size(total_size, content_size))
+        .collect();
+    let used_size: usize = sizes.iter().sum();
+    if used_size < total_size {
+        let weights: Sizes = children
+            .clone()
+            .zip(content_sizes)
+            .map(|(child, content_size)| match (child.flex, content_size) {
+                (FlexBasis::Content, None) => cmp::max(child.grow, 1),
+                _ => child.grow,
+            })
+            .collect();
+        let limits: Sizes = children
+            .map(|child| {
+                child
+                    .max_size
+                    .map_or(usize::MAX, |max_size| child.clamp(max_size))
+            })
+            .collect();
+        flex(&mut sizes, &weights, &limits, total_size - used_size, true);
+    } else if used_size > total_size {
+        let weights: Sizes = children
+            .clone()
+            .zip(sizes.iter())
+            .map(|(child, size)| child.shrink * size)
+            .collect();
+        let limits: Sizes = children.map(|child| child.min_size).collect();
+        flex(&mut sizes, &weights, &limits, used_size - total_size, false);
+    }
+    sizes
+}
+
+/// Grows or shrinks `sizes` by `amount` in total, in proportion to `weights`
+/// and without going past `limits`. Any space that can't be divided evenly
+/// goes to the first items.
+fn flex(sizes: &mut [usize], weights: &[usize], limits: &[usize], mut amount: usize, grow: bool) {
+    let mut frozen: SmallVec<[bool; ITEMS_INLINE_SIZE]> =
+        weights.iter().map(|&weight| weight == 0).collect();
+    loop {
+        let total_weight: usize = (0..sizes.len())
+            .filter(|&index| !frozen[index])
+            .map(|index| weights[index])
+            .sum();
+        if total_weight == 0 || amount == 0 {
+            return;
         }
-        (stretched_budget, num_stretched_children, total_fixed_size)
-    };
 
-    // Divvy up the space equaly between stretched components.
-    let stretched_size = if num_stretched_children > 0 {
-        stretched_budget / num_stretched_children
-    } else {
-        0
-    };
-    let mut remainder =
-        total_size.saturating_sub(num_stretched_children * stretched_size + total_fixed_size);
-    let mut remaining_size = total_size;
-
-    children
-        .map(move |child| match child.flex {
-            FlexBasis::Auto => {
-                let offset = total_size - remaining_size;
-                let size = if remainder > 0 {
+        let mut remainder = amount
+            - (0..sizes.len())
+                .filter(|&index| !frozen[index])
+                .map(|index| amount * weights[index] / total_weight)
+                .sum::<usize>();
+        let deltas: Sizes = (0..sizes.len())
+            .map(|index| {
+                if frozen[index] {
+                    return 0;
+                }
+                let mut delta = amount * weights[index] / total_weight;
+                if remainder > 0 {
                     remainder -= 1;
-                    stretched_size + 1
+                    delta += 1;
+                }
+                delta
+            })
+            .collect();
+
+        // Items that would go past their limit are fixed at the limit and the
+        // rest is divided again between the other items
+        let mut violated = false;
+        for index in 0..sizes.len() {
+            if frozen[index] {
+                continue;
+            }
+            let past_limit = if grow {
+                sizes[index] + deltas[index] > limits[index]
+            } else {
+                sizes[index] < limits[index] + deltas[index]
+            };
+            if past_limit {
+                amount -= if grow {
+                    limits[index] - sizes[index]
                 } else {
-                    stretched_size
+                    sizes[index] - limits[index]
                 };
-                remaining_size -= size;
-                (offset, size)
+                sizes[index] = limits[index];
+                frozen[index] = true;
+                violated = true;
             }
-            FlexBasis::Fixed(size) => {
-                let offset = total_size - remaining_size;
-                let size = cmp::min(remaining_size, size);
-                remaining_size -= size;
-                (offset, size)
+        }
+        if !violated {
+            for (size, delta) in sizes.iter_mut().zip(deltas) {
+                if grow {
+                    *size += delta;
+                } else {
+                    *size -= delta;
+                }
             }
-        })
-        .map(move |(offset, size)| match direction {
-            FlexDirection::Row | FlexDirection::RowReverse => Rect::new(
-                Position::new(frame.origin.x + offset, frame.origin.y),
-                Size::new(size, frame.size.height),
-            ),
-            FlexDirection::Column | FlexDirection::ColumnReverse => Rect::new(
-                Position::new(frame.origin.x, frame.origin.y + offset),
-                Size::new(frame.size.width, size),
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::text::{Text, TextProperties, TextWrap},
+        testing::TestBackend,
+    };
+
+    fn sizes(total_size: usize, items: &[Item]) -> Vec<usize> {
+        flex_sizes(total_size, items.iter(), &vec![None; items.len()]).into_vec()
+    }
+
+    fn empty() -> Layout {
+        Container::empty(FlexDirection::Row).into()
+    }
+
+    #[test]
+    fn items_grow_and_shrink_within_their_limits() {
+        // Equal split, the remainder goes to the first items
+        assert_eq!(
+            sizes(
+                10,
+                &[
+                    Item::auto(empty()),
+                    Item::auto(empty()),
+                    Item::auto(empty())
+                ]
             ),
-        })
+            [4, 3, 3]
+        );
+        assert_eq!(
+            sizes(10, &[Item::fixed(4)(empty()), Item::auto(empty())]),
+            [4, 6]
+        );
+
+        // 2:1 split of the space left after a percentage with a minimum size
+        let items = [
+            Item::percent(30)(empty()).min_size(20),
+            Item::auto(empty()).grow(2),
+            Item::auto(empty()),
+        ];
+        assert_eq!(sizes(100, &items), [30, 47, 23]);
+        assert_eq!(sizes(50, &items), [20, 20, 10]);
+
+        // A maximum size passes the space on to the other items
+        let items = [Item::auto(empty()).max_size(2), Item::auto(empty())];
+        assert_eq!(sizes(10, &items), [2, 8]);
+
+        // Items shrink in proportion to their basis, down to their minimum
+        let items = [
+            Item::fixed(10)(empty()).shrink(1),
+            Item::fixed(30)(empty()).shrink(1).min_size(25),
+        ];
+        assert_eq!(sizes(36, &items), [9, 27]);
+        assert_eq!(sizes(30, &items), [5, 25]);
+        assert_eq!(sizes(20, &items), [0, 25]);
+    }
+
+    #[test]
+    fn items_are_justified_aligned_and_spaced() {
+        let frame = Rect::new(Position::new(1, 1), Size::new(10, 4));
+        let splits = |container: Container| {
+            splits_iter(frame, &container, container.children.iter())
+                .map(|rect| {
+                    (
+                        rect.origin.x,
+                        rect.origin.y,
+                        rect.size.width,
+                        rect.size.height,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let row = || Container::row([Item::fixed(2)(empty()), Item::fixed(2)(empty())]);
+
+        assert_eq!(splits(row()), [(1, 1, 2, 4), (3, 1, 2, 4)]);
+        assert_eq!(
+            splits(row().justify(Justify::Center)),
+            [(4, 1, 2, 4), (6, 1, 2, 4)]
+        );
+        assert_eq!(
+            splits(row().justify(Justify::End)),
+            [(7, 1, 2, 4), (9, 1, 2, 4)]
+        );
+        assert_eq!(
+            splits(row().justify(Justify::SpaceBetween)),
+            [(1, 1, 2, 4), (9, 1, 2, 4)]
+        );
+        assert_eq!(
+            splits(row().justify(Justify::SpaceAround)),
+            [(2, 1, 2, 4), (7, 1, 2, 4)]
+        );
+
+        // Cross sizes are positioned by the container's or the item's alignment
+        let container = Container::row([
+            Item::fixed(2)(empty()).cross_size(2),
+            Item::fixed(2)(empty()).cross_size(2).align_self(Align::End),
+        ])
+        .align(Align::Center);
+        assert_eq!(splits(container), [(1, 2, 2, 2), (3, 3, 2, 2)]);
+
+        // Margins are outside of the item's size, padding is inside
+        let container = Container::column([
+            Item::fixed(1)(empty()).margin(1),
+            Item::auto(empty()).padding(Edges::symmetric(0, 2)),
+        ]);
+        assert_eq!(splits(container), [(2, 2, 8, 1), (3, 4, 6, 1)]);
+    }
+
+    #[test]
+    fn children_are_identified_by_index_or_key() {
+        let hasher = DefaultHasher::new();
+        let unkeyed = || Text::with(TextProperties::new()).0;
+        let keyed = |key: usize| Text::with_key(key, TextProperties::new()).0;
+
+        // Unkeyed siblings are told apart by their index
+        assert_ne!(
+            unkeyed().item_hash(&hasher, 0),
+            unkeyed().item_hash(&hasher, 1)
+        );
+        assert_eq!(
+            unkeyed().item_hash(&hasher, 1),
+            unkeyed().item_hash(&hasher, 1)
+        );
+
+        // Keyed siblings keep their identity when reordered
+        assert_eq!(
+            keyed(7).item_hash(&hasher, 0),
+            keyed(7).item_hash(&hasher, 3)
+        );
+    }
+
+    #[test]
+    fn grid_columns_line_up_across_rows() {
+        let text = |content: &str| Text::with(TextProperties::new().content(content));
+        let app = TestBackend::new(
+            Size::new(16, 5),
+            Grid::new(
+                [Track::Fixed(5), Track::Fraction(2), Track::Auto],
+                [Track::Fixed(1), Track::Fixed(1), Track::Auto],
+            )
+            .column_gap(1)
+            .row_gap(1)
+            .cells([
+                GridCell::new(0, 0, text("name")),
+                GridCell::new(0, 1, text("zi")),
+                GridCell::new(0, 2, text("ok")),
+                GridCell::new(1, 0, text("license")),
+                GridCell::new(1, 1, text("MIT")),
+                GridCell::new(2, 0, text("a spanning cell")).span(1, 3),
+            ])
+            .into(),
+        );
+        let grapheme = |x, y| app.textel(x, y).map(|textel| textel.grapheme.as_str());
+
+        // Tracks are 5, 6 and 3 columns wide, with gaps left undrawn
+        assert_eq!(grapheme(5, 0), Some(""));
+        assert_eq!(grapheme(6, 0), Some("z"));
+        app.assert_lines(&["name  zi     ok", "", "licen MIT", "", "a spanning cell"]);
+    }
+
+    #[test]
+    fn content_items_are_sized_by_their_text() {
+        let text = |content: &str| TextProperties::new().content(content);
+        let app = TestBackend::new(
+            Size::new(12, 6),
+            Layout::column([
+                Item::content(Text::with(text("one\ntwo"))),
+                Item::content(Text::with(text("a few words here").wrap(TextWrap::Word))),
+                Item::auto(Text::with(text("rest"))),
+                Item::content(Text::with(text("end"))).align_self(Align::End),
+            ]),
+        );
+        // The last item is only as wide as its text, at the end of the row
+        app.assert_lines(&["one", "two", "a few words", "here", "rest", "         end"]);
+    }
 }
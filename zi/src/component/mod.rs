@@ -1,24 +1,34 @@
 //! Defines the `Component` trait and related types.
 pub mod bindings;
+pub(crate) mod context;
 pub mod layout;
+pub mod overrides;
+pub(crate) mod tasks;
 pub(crate) mod template;
 
 pub use self::layout::{ComponentExt, Layout};
 
 use std::{
-    any::{self, TypeId},
+    any::{self, Any, TypeId},
+    borrow::Cow,
     fmt,
+    future::Future,
     marker::PhantomData,
     rc::Rc,
+    sync::Arc,
 };
 
 use self::{
     bindings::{Bindings, NamedBindingQuery},
+    context::SharedContexts,
+    tasks::{ComponentTasks, TaskOutput},
     template::{ComponentId, DynamicMessage},
 };
 use crate::{
-    app::{ComponentMessage, MessageSender},
-    terminal::{KeyEvent, Rect},
+    app::{CommandInfo, ComponentMessage, MessageSender},
+    store::{SharedStore, State},
+    terminal::{KeyEvent, Rect, Size},
+    theme::{SharedTheme, Theme},
 };
 
 /// Components are the building blocks of the UI in Zi.
@@ -94,9 +104,33 @@ pub trait Component: Sized + 'static {
 
     fn notify_binding_queries(&self, _queries: &[Option<NamedBindingQuery>], _keys: &[KeyEvent]) {}
 
+    /// Called when a focusable component receives focus from the runtime. See
+    /// [`Bindings::set_focusable`](bindings/struct.Bindings.html#method.set_focusable).
+    fn focus_gained(&mut self) -> ShouldRender {
+        ShouldRender::No
+    }
+
+    /// Called when a component loses focus managed by the runtime, while it is
+    /// still mounted.
+    fn focus_lost(&mut self) -> ShouldRender {
+        ShouldRender::No
+    }
+
     fn tick(&self) -> Option<Self::Message> {
         None
     }
+
+    /// Returns the size the component would like to have when `available`
+    /// space is left for it. Used to size items with
+    /// [`FlexBasis::Content`](layout/enum.FlexBasis.html#variant.Content).
+    ///
+    /// Size hints are computed while laying out the parent, before the
+    /// component is created or receives its new properties, so they are
+    /// computed from the properties rather than from the component itself.
+    /// The default implementation returns `None`, i.e. no preference.
+    fn size_hint(_properties: &Self::Properties, _available: Size) -> Option<Size> {
+        None
+    }
 }
 
 /// Callback wrapper. Useful for passing callbacks in child components
@@ -169,6 +203,7 @@ where
 pub struct ComponentLink<ComponentT> {
     sender: Box<dyn MessageSender>,
     component_id: ComponentId,
+    runtime: ComponentRuntime,
     _component: PhantomData<fn() -> ComponentT>,
 }
 
@@ -200,11 +235,221 @@ impl<ComponentT: Component> ComponentLink<ComponentT> {
         self.sender.send(ComponentMessage(LinkMessage::Exit));
     }
 
-    pub(crate) fn new(sender: Box<dyn MessageSender>, component_id: ComponentId) -> Self {
+    /// Requests focus for the linked component. The request is ignored unless
+    /// the component is focusable, see
+    /// [`Bindings::set_focusable`](bindings/struct.Bindings.html#method.set_focusable).
+    pub fn request_focus(&self) {
+        self.send_focus(FocusRequest::Request(self.component_id));
+    }
+
+    /// Gives up focus if the linked component is currently focused. No
+    /// component has focus afterwards, until focus is requested or moved.
+    pub fn release_focus(&self) {
+        self.send_focus(FocusRequest::Release(self.component_id));
+    }
+
+    /// Moves focus to the next focusable component, as if `Tab` was pressed.
+    pub fn focus_next(&self) {
+        self.send_focus(FocusRequest::Next);
+    }
+
+    /// Moves focus to the previous focusable component, as if `BackTab` was
+    /// pressed.
+    pub fn focus_previous(&self) {
+        self.send_focus(FocusRequest::Previous);
+    }
+
+    /// Runs a command by name, as if its key binding was pressed. See
+    /// [`App::run_command`](../app/struct.App.html#method.run_command).
+    pub fn run_command(&self, name: impl Into<Cow<'static, str>>) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::RunCommand(None, name.into())));
+    }
+
+    /// Like `run_command`, but skips the commands of the linked component and
+    /// its descendants, so the command runs on a component outside of it.
+    /// This lets a component such as a command palette run the commands it
+    /// lists without shadowing them with its own.
+    pub fn run_command_outside(&self, name: impl Into<Cow<'static, str>>) {
+        self.sender.send(ComponentMessage(LinkMessage::RunCommand(
+            Some(self.component_id),
+            name.into(),
+        )));
+    }
+
+    /// Requests the commands of all mounted components, see
+    /// [`App::commands`](../app/struct.App.html#method.commands). The
+    /// commands are delivered to the component's update method as the message
+    /// returned by `callback`.
+    ///
+    /// Commands of the linked component and its descendants are left out.
+    pub fn request_commands(
+        &self,
+        callback: impl FnOnce(Vec<CommandInfo>) -> ComponentT::Message + Send + 'static,
+    ) {
+        self.sender.send(ComponentMessage(LinkMessage::Commands(
+            self.component_id,
+            Box::new(move |commands| DynamicMessage(Box::new(callback(commands)))),
+        )));
+    }
+
+    /// Starts recording a keyboard macro. Ignored unless macros are enabled,
+    /// see [`App::start_macro`](../app/struct.App.html#method.start_macro).
+    pub fn start_macro(&self, name: impl Into<String>) {
+        self.send_macro(MacroRequest::Start(name.into()));
+    }
+
+    /// Stops recording a keyboard macro. The keys of the binding that called
+    /// this method are not part of the macro.
+    pub fn stop_macro(&self) {
+        self.send_macro(MacroRequest::Stop);
+    }
+
+    /// Replays a keyboard macro `count` times. Ignored when sent while
+    /// replaying a macro.
+    pub fn replay_macro(&self, name: impl Into<String>, count: usize) {
+        self.send_macro(MacroRequest::Replay(Some(name.into()), count));
+    }
+
+    /// Replays the most recently recorded keyboard macro `count` times.
+    pub fn replay_last_macro(&self, count: usize) {
+        self.send_macro(MacroRequest::Replay(None, count));
+    }
+
+    /// Returns the active theme, see the [`theme`](theme/index.html) module.
+    pub fn theme(&self) -> Arc<Theme> {
+        self.runtime.theme.get()
+    }
+
+    /// Replaces the active theme and re-renders all components. See
+    /// [`App::set_theme`](../app/struct.App.html#method.set_theme).
+    pub fn set_theme(&self, theme: Theme) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::SetTheme(theme)));
+    }
+
+    /// Provides a value to all descendants of the linked component, which
+    /// read it with [`context`](#method.context). Call this method in `view`.
+    ///
+    /// Values are keyed by their type and a descendant sees the value of the
+    /// nearest ancestor that provides one. A value is provided until a later
+    /// `view` doesn't provide it anymore. When it changes, all descendants
+    /// are re-rendered.
+    pub fn provide<ValueT: PartialEq + Send + Sync + 'static>(&self, value: ValueT) {
+        self.runtime.contexts.provide(value);
+    }
+
+    /// Returns the value of a type provided by the nearest ancestor, see
+    /// [`provide`](#method.provide). Values are available in `create` and
+    /// are kept up to date while the component is mounted.
+    pub fn context<ValueT: Send + Sync + 'static>(&self) -> Option<Arc<ValueT>> {
+        self.runtime.contexts.get()
+    }
+
+    /// Dispatches an action to the app's store, see the
+    /// [`store`](store/index.html) module. Components whose selected slice of
+    /// the state changes are sent a message before the app is redrawn.
+    pub fn dispatch<ActionT: Send + 'static>(&self, action: ActionT) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::Dispatch(Box::new(action))));
+    }
+
+    /// Returns a slice of the store's state, or `None` if the app doesn't have
+    /// a store holding a `StateT`.
+    pub fn select<StateT: State, SliceT>(
+        &self,
+        selector: impl FnOnce(&StateT) -> SliceT,
+    ) -> Option<SliceT> {
+        self.runtime
+            .store
+            .read(|store| store.select(selector))
+            .and_then(|slice| slice)
+    }
+
+    /// Subscribes the linked component to a slice of the store's state and
+    /// returns the current slice. Whenever an action changes the slice, the
+    /// component is sent the message returned by `message`. Call this method
+    /// in `create`, the subscription lasts until the component is unmounted.
+    ///
+    /// Returns `None`, without subscribing, if the app doesn't have a store
+    /// holding a `StateT`.
+    pub fn subscribe<StateT: State, SliceT: Clone + PartialEq + Send + Sync + 'static>(
+        &self,
+        selector: impl Fn(&StateT) -> SliceT + Send + Sync + 'static,
+        message: impl Fn(SliceT) -> ComponentT::Message + Send + Sync + 'static,
+    ) -> Option<SliceT> {
+        let component_id = self.component_id;
+        self.runtime
+            .store
+            .write(|store| {
+                let mut last = store.select(&selector)?;
+                let current = last.clone();
+                store.subscribe(
+                    component_id,
+                    Box::new(move |state| {
+                        let slice = selector(state.downcast_ref()?);
+                        if slice == last {
+                            return None;
+                        }
+                        last = slice.clone();
+                        Some(DynamicMessage(Box::new(message(slice))))
+                    }),
+                );
+                Some(current)
+            })
+            .and_then(|current| current)
+    }
+
+    /// Runs a future in the background, e.g. a file read or a request, and
+    /// sends its output to the component as a message. The future is run by
+    /// the app's [`TaskSpawner`](app/trait.TaskSpawner.html), which for
+    /// zi-term is its tokio runtime.
+    ///
+    /// The task is cancelled when the component is unmounted, i.e. the future
+    /// is dropped without being polled again and no message is sent.
+    pub fn spawn(&self, future: impl Future<Output = ComponentT::Message> + Send + 'static) {
+        self.runtime
+            .tasks
+            .spawn(self.sender.clone_box(), self.component_id, async move {
+                DynamicMessage(Box::new(future.await))
+            });
+    }
+
+    /// Like [`spawn`](#method.spawn), but runs a function which may block,
+    /// e.g. a subprocess, on a thread where blocking is acceptable.
+    ///
+    /// A running function can't be interrupted. If the component is unmounted
+    /// before it returns, its output is discarded instead.
+    pub fn spawn_blocking(&self, function: impl FnOnce() -> ComponentT::Message + Send + 'static) {
+        self.runtime
+            .tasks
+            .spawn_blocking(self.sender.clone_box(), self.component_id, move || {
+                DynamicMessage(Box::new(function()))
+            });
+    }
+
+    #[inline]
+    fn send_focus(&self, request: FocusRequest) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::Focus(request)));
+    }
+
+    #[inline]
+    fn send_macro(&self, request: MacroRequest) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::Macro(request)));
+    }
+
+    pub(crate) fn new(
+        sender: Box<dyn MessageSender>,
+        component_id: ComponentId,
+        runtime: ComponentRuntime,
+    ) -> Self {
         assert_eq!(TypeId::of::<ComponentT>(), component_id.type_id());
         Self {
             sender,
             component_id,
+            runtime,
             _component: PhantomData,
         }
     }
@@ -215,6 +460,7 @@ impl<ComponentT> Clone for ComponentLink<ComponentT> {
         Self {
             sender: self.sender.clone_box(),
             component_id: self.component_id,
+            runtime: self.runtime.clone(),
             _component: PhantomData,
         }
     }
@@ -226,6 +472,16 @@ impl<ComponentT> PartialEq for ComponentLink<ComponentT> {
     }
 }
 
+/// The parts of the app's runtime shared with the link of a mounted
+/// component.
+#[derive(Clone, Debug)]
+pub(crate) struct ComponentRuntime {
+    pub theme: SharedTheme,
+    pub contexts: SharedContexts,
+    pub store: SharedStore,
+    pub tasks: ComponentTasks,
+}
+
 /// Type to indicate whether a component should be rendered again.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ShouldRender {
@@ -267,9 +523,33 @@ impl std::ops::BitAnd for ShouldRender {
 
 pub(crate) enum LinkMessage {
     Component(ComponentId, DynamicMessage),
+    Focus(FocusRequest),
+    Commands(ComponentId, CommandsCallback),
+    RunCommand(Option<ComponentId>, Cow<'static, str>),
+    Macro(MacroRequest),
+    SetTheme(Theme),
+    Dispatch(Box<dyn Any + Send>),
+    Task(TaskOutput),
     Exit,
 }
 
+pub(crate) type CommandsCallback = Box<dyn FnOnce(Vec<CommandInfo>) -> DynamicMessage + Send>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum MacroRequest {
+    Start(String),
+    Stop,
+    Replay(Option<String>, usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FocusRequest {
+    Request(ComponentId),
+    Release(ComponentId),
+    Next,
+    Previous,
+}
+
 impl std::fmt::Debug for LinkMessage {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(formatter, "LinkMessage::")?;
@@ -279,6 +559,13 @@ impl std::fmt::Debug for LinkMessage {
                 "Component({:?}, DynamicMessage(...) @ {:?})",
                 id, &*message.0 as *const _
             ),
+            Self::Focus(request) => write!(formatter, "Focus({:?})", request),
+            Self::Commands(id, _) => write!(formatter, "Commands({:?}, ...)", id),
+            Self::RunCommand(id, name) => write!(formatter, "RunCommand({:?}, {:?})", id, name),
+            Self::Macro(request) => write!(formatter, "Macro({:?})", request),
+            Self::SetTheme(_) => write!(formatter, "SetTheme(...)"),
+            Self::Dispatch(_) => write!(formatter, "Dispatch(...)"),
+            Self::Task(output) => write!(formatter, "Task({:?})", output),
             Self::Exit => write!(formatter, "Exit"),
         }
     }
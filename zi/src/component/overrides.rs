@@ -0,0 +1,309 @@
+//! User defined key bindings which replace the defaults of components.
+//!
+//! Overrides map command names to key sequences. They are applied to the
+//! keymap of every component when its bindings are first set up, so the
+//! components themselves don't need to know about them. Overrides can be
+//! built in code or loaded from a file with one binding per line:
+//!
+//! ```text
+//! # Bindings outside of a section apply to commands of any component
+//! cancel = C-g
+//!
+//! # Bindings in a section apply only to components of that type. The
+//! # section name is either the full type name or the last path segment,
+//! # and the full type name takes precedence.
+//! [Select]
+//! next-item = C-n
+//! next-item = <down>
+//! previous-item = C-p
+//! ```
+//!
+//! Each line binds one key sequence, repeat a command to bind more than one.
+//! The first binding for a command replaces all of its default bindings. A
+//! command with an empty key sequence (`next-item =`) is left unbound.
+
+use std::{collections::HashMap, error::Error, fmt, fs, io, path::Path, str::FromStr};
+
+use super::bindings::{KeyPattern, Keymap};
+
+/// Key bindings that replace the default bindings of commands.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeymapOverrides {
+    global: CommandBindings,
+    components: HashMap<String, CommandBindings>,
+}
+
+type CommandBindings = HashMap<String, Vec<KeyPattern>>;
+
+impl KeymapOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads overrides from a file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Returns `true` if there are no overrides.
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.components.is_empty()
+    }
+
+    /// Binds a key pattern to a command of any component.
+    pub fn bind(
+        &mut self,
+        command: impl Into<String>,
+        pattern: impl Into<KeyPattern>,
+    ) -> &mut Self {
+        push_binding(&mut self.global, command.into(), Some(pattern.into()));
+        self
+    }
+
+    /// Binds a key pattern to a command of a specific component type.
+    pub fn bind_component(
+        &mut self,
+        component: impl Into<String>,
+        command: impl Into<String>,
+        pattern: impl Into<KeyPattern>,
+    ) -> &mut Self {
+        push_binding(
+            self.components.entry(component.into()).or_default(),
+            command.into(),
+            Some(pattern.into()),
+        );
+        self
+    }
+
+    /// Removes all bindings of a command of any component.
+    pub fn unbind(&mut self, command: impl Into<String>) -> &mut Self {
+        push_binding(&mut self.global, command.into(), None);
+        self
+    }
+
+    /// Returns the overridden key bindings of a command for a component type,
+    /// or `None` if the command keeps its default bindings.
+    ///
+    /// If several sections match the type, the most specific one which binds
+    /// the command wins: the full type name, then the name without generic
+    /// parameters, then the last path segment.
+    pub fn get(&self, type_name: &str, command: &str) -> Option<&[KeyPattern]> {
+        section_names(type_name)
+            .iter()
+            .filter_map(|section| self.components.get(*section))
+            .find_map(|bindings| bindings.get(command))
+            .or_else(|| self.global.get(command))
+            .map(Vec::as_slice)
+    }
+
+    /// Applies the overrides to the keymap of a component.
+    ///
+    /// Bindings that overlap with other bindings of the component are skipped
+    /// with a warning.
+    pub fn apply(&self, type_name: &str, keymap: &mut Keymap) {
+        // Overridden bindings go to the layer where the command was bound
+        let commands: Vec<_> = keymap
+            .commands()
+            .filter_map(|(command_id, command)| {
+                let patterns = self.get(type_name, command)?;
+                Some((command_id, keymap.command_layer(command_id), patterns))
+            })
+            .collect();
+        for &(command_id, _, _) in commands.iter() {
+            keymap.unbind_command(command_id);
+        }
+        for (command_id, layer_id, patterns) in commands {
+            for pattern in patterns {
+                let result = match layer_id {
+                    Some(layer_id) => {
+                        keymap.try_bind_layer_command(layer_id, command_id, pattern.clone())
+                    }
+                    None => keymap.try_bind_command(command_id, pattern.clone()),
+                };
+                if let Err(conflict) = result {
+                    log::warn!(
+                        "Ignoring key binding override for {}: {}",
+                        type_name,
+                        conflict
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for KeymapOverrides {
+    type Err = ParseOverridesError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut overrides = Self::new();
+        let mut section = None;
+        for (index, line) in input.lines().enumerate() {
+            let error = |message: String| ParseOverridesError {
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = line[1..line.len() - 1].trim();
+                if name.is_empty() {
+                    return Err(error("empty section name".into()));
+                }
+                section = Some(name.to_owned());
+                continue;
+            }
+
+            let (command, keys) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected `command = keys`, found `{}`", line)))?;
+            let command = command.trim();
+            if command.is_empty() {
+                return Err(error("empty command name".into()));
+            }
+            let pattern = match keys.trim() {
+                "" => None,
+                keys => Some(
+                    keys.parse()
+                        .map_err(|error_| error(format!("{}", error_)))?,
+                ),
+            };
+
+            let bindings = match section {
+                Some(ref component) => overrides.components.entry(component.clone()).or_default(),
+                None => &mut overrides.global,
+            };
+            push_binding(bindings, command.to_owned(), pattern);
+        }
+        Ok(overrides)
+    }
+}
+
+/// Error returned when parsing keymap overrides fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOverridesError {
+    /// The line number where the error occurred, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseOverridesError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseOverridesError {}
+
+fn push_binding(bindings: &mut CommandBindings, command: String, pattern: Option<KeyPattern>) {
+    let patterns = bindings.entry(command).or_default();
+    patterns.extend(pattern);
+}
+
+/// Returns the section names matching a type, most specific first.
+fn section_names(type_name: &str) -> [&str; 3] {
+    let without_generics = type_name.split('<').next().unwrap_or(type_name);
+    let short_name = without_generics
+        .rsplit("::")
+        .next()
+        .unwrap_or(without_generics);
+    [type_name, without_generics, short_name]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::bindings::BindingQuery;
+    use crate::terminal::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn overrides_replace_default_bindings() {
+        let overrides: KeymapOverrides = "
+            # Applies to any component
+            cancel = C-g
+
+            [Select]
+            next-item = C-n
+            next-item = <down>
+            previous-item =
+        "
+        .parse()
+        .unwrap();
+
+        let mut keymap = Keymap::new();
+        let next_id = keymap.add("next-item", [KeyEvent::from(KeyCode::Char('j'))]);
+        let previous_id = keymap.add("previous-item", [KeyEvent::from(KeyCode::Char('k'))]);
+        let cancel_id = keymap.add("cancel", [KeyEvent::from(KeyCode::Esc)]);
+        overrides.apply("zi::components::select::Select", &mut keymap);
+
+        let ctrl = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::CONTROL);
+        assert_eq!(
+            keymap.check_sequence(&[ctrl('n')]),
+            Some(&BindingQuery::Match(next_id))
+        );
+        assert_eq!(
+            keymap.check_sequence(&[KeyEvent::from(KeyCode::Down)]),
+            Some(&BindingQuery::Match(next_id))
+        );
+        assert_eq!(
+            keymap.check_sequence(&[ctrl('g')]),
+            Some(&BindingQuery::Match(cancel_id))
+        );
+        assert_eq!(
+            keymap.check_sequence(&[KeyEvent::from(KeyCode::Char('j'))]),
+            None
+        );
+        assert_eq!(
+            keymap.check_sequence(&[KeyEvent::from(KeyCode::Char('k'))]),
+            None
+        );
+        assert_eq!(keymap.check_sequence(&[KeyEvent::from(KeyCode::Esc)]), None);
+        assert!(keymap.command_id("previous-item") == Some(previous_id));
+
+        // Section bindings don't apply to other components
+        let mut keymap = Keymap::new();
+        keymap.add("next-item", [KeyEvent::from(KeyCode::Char('j'))]);
+        overrides.apply("my_app::Sidebar", &mut keymap);
+        assert!(keymap
+            .check_sequence(&[KeyEvent::from(KeyCode::Char('j'))])
+            .is_some());
+
+        // The most specific section matching a type wins
+        let overrides: KeymapOverrides = "
+            [Select]
+            cancel = a
+            [zi::components::select::Select]
+            cancel = b
+            [zi::components::select::Select<u8>]
+            cancel = c
+        "
+        .parse()
+        .unwrap();
+        let cancel_key = |type_name| {
+            let mut keymap = Keymap::new();
+            keymap.add("cancel", [KeyEvent::from(KeyCode::Esc)]);
+            overrides.apply(type_name, &mut keymap);
+            ['a', 'b', 'c'].into_iter().find(|&char| {
+                keymap
+                    .check_sequence(&[KeyEvent::from(KeyCode::Char(char))])
+                    .is_some()
+            })
+        };
+        assert_eq!(cancel_key("zi::components::select::Select<u8>"), Some('c'));
+        assert_eq!(cancel_key("zi::components::select::Select<u16>"), Some('b'));
+        assert_eq!(cancel_key("my_app::Select"), Some('a'));
+
+        assert_eq!(
+            "next-item C-n".parse::<KeymapOverrides>(),
+            Err(ParseOverridesError {
+                line: 1,
+                message: "expected `command = keys`, found `next-item C-n`".into()
+            })
+        );
+    }
+}
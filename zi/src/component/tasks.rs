@@ -0,0 +1,413 @@
+//! Tasks spawned by components, see
+//! [`ComponentLink::spawn`](../struct.ComponentLink.html#method.spawn).
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock, Weak,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use super::{
+    template::{ComponentId, DynamicMessage},
+    LinkMessage,
+};
+use crate::app::{ComponentMessage, MessageSender, Task, TaskSpawner};
+
+/// The task spawner of an app, if any, shared with the links of its
+/// components.
+#[derive(Clone, Default)]
+pub(crate) struct SharedSpawner(Arc<RwLock<Option<Arc<dyn TaskSpawner>>>>);
+
+impl SharedSpawner {
+    pub(crate) fn set(&self, spawner: Arc<dyn TaskSpawner>) {
+        *self.0.write().expect("spawner lock to not be poisoned") = Some(spawner);
+    }
+
+    fn get(&self) -> Option<Arc<dyn TaskSpawner>> {
+        self.0
+            .read()
+            .expect("spawner lock to not be poisoned")
+            .clone()
+    }
+}
+
+impl fmt::Debug for SharedSpawner {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "SharedSpawner({:?})", self.get())
+    }
+}
+
+/// The tasks spawned by a mounted component, shared with its link.
+#[derive(Clone, Debug)]
+pub(crate) struct ComponentTasks {
+    spawner: SharedSpawner,
+    /// The cancellation flags of the tasks which haven't finished yet. A task
+    /// owns its flag, so the entry of a finished task can't be upgraded.
+    running: Arc<Mutex<Vec<Weak<Cancellation>>>>,
+}
+
+impl ComponentTasks {
+    pub(crate) fn new(spawner: SharedSpawner) -> Self {
+        Self {
+            spawner,
+            running: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Runs a future with the app's spawner and sends its result, unless the
+    /// component is unmounted first.
+    pub(crate) fn spawn(
+        &self,
+        sender: Box<dyn MessageSender>,
+        component_id: ComponentId,
+        future: impl Future<Output = DynamicMessage> + Send + 'static,
+    ) {
+        let spawner = match self.spawner() {
+            Some(spawner) => spawner,
+            None => return,
+        };
+        let cancellation = self.track();
+        spawner.spawn(Box::pin(Cancellable {
+            task: Box::pin({
+                let cancellation = cancellation.clone();
+                async move {
+                    let message = future.await;
+                    sender.send(TaskOutput::message(cancellation, component_id, message));
+                }
+            }),
+            cancellation,
+        }));
+    }
+
+    /// Runs a blocking function with the app's spawner and sends its result,
+    /// unless the component is unmounted before the result is delivered.
+    pub(crate) fn spawn_blocking(
+        &self,
+        sender: Box<dyn MessageSender>,
+        component_id: ComponentId,
+        function: impl FnOnce() -> DynamicMessage + Send + 'static,
+    ) {
+        let spawner = match self.spawner() {
+            Some(spawner) => spawner,
+            None => return,
+        };
+        let cancellation = self.track();
+        spawner.spawn_blocking(Box::new(move || {
+            if cancellation.is_cancelled() {
+                return;
+            }
+            let message = function();
+            sender.send(TaskOutput::message(cancellation, component_id, message));
+        }));
+    }
+
+    /// Cancels all running tasks, called when the component is unmounted.
+    pub(crate) fn cancel(&self) {
+        let running = std::mem::take(&mut *self.running.lock().expect("lock to not be poisoned"));
+        for cancellation in running.iter().filter_map(Weak::upgrade) {
+            cancellation.cancel();
+        }
+    }
+
+    fn spawner(&self) -> Option<Arc<dyn TaskSpawner>> {
+        let spawner = self.spawner.get();
+        if spawner.is_none() {
+            log::warn!("Dropping a task spawned by a component of an app without a task spawner");
+        }
+        spawner
+    }
+
+    fn track(&self) -> Arc<Cancellation> {
+        let cancellation = Arc::new(Cancellation::default());
+        let mut running = self.running.lock().expect("lock to not be poisoned");
+        running.retain(|cancellation| cancellation.strong_count() > 0);
+        running.push(Arc::downgrade(&cancellation));
+        cancellation
+    }
+}
+
+/// The result of a task, on its way to the component which spawned it.
+pub(crate) struct TaskOutput {
+    cancellation: Arc<Cancellation>,
+    component_id: ComponentId,
+    message: DynamicMessage,
+}
+
+impl TaskOutput {
+    fn message(
+        cancellation: Arc<Cancellation>,
+        component_id: ComponentId,
+        message: DynamicMessage,
+    ) -> ComponentMessage {
+        ComponentMessage(LinkMessage::Task(Self {
+            cancellation,
+            component_id,
+            message,
+        }))
+    }
+
+    /// Returns the message and its recipient, or `None` if the task was
+    /// cancelled since it finished. A cancelled task's component was
+    /// unmounted, while the id may already belong to a new component.
+    pub(crate) fn into_message(self) -> Option<(ComponentId, DynamicMessage)> {
+        if self.cancellation.is_cancelled() {
+            None
+        } else {
+            Some((self.component_id, self.message))
+        }
+    }
+}
+
+impl fmt::Debug for TaskOutput {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "TaskOutput({:?}, cancelled: {})",
+            self.component_id,
+            self.cancellation.is_cancelled()
+        )
+    }
+}
+
+#[derive(Default)]
+struct Cancellation {
+    cancelled: AtomicBool,
+    /// The waker of the latest poll, woken on cancellation so that a pending
+    /// task is dropped promptly.
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Cancellation {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        if let Some(waker) = self.waker.lock().expect("lock to not be poisoned").take() {
+            waker.wake();
+        }
+    }
+
+    #[inline]
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// A task which completes early once cancelled, without polling the wrapped
+/// task again.
+struct Cancellable {
+    task: Task,
+    cancellation: Arc<Cancellation>,
+}
+
+impl Future for Cancellable {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        *self
+            .cancellation
+            .waker
+            .lock()
+            .expect("lock to not be poisoned") = Some(context.waker().clone());
+        // Checked after storing the waker, so a concurrent cancellation either
+        // sees the waker or is seen here
+        if self.cancellation.is_cancelled() {
+            return Poll::Ready(());
+        }
+        self.task.as_mut().poll(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{
+        components::text::{Text, TextProperties},
+        terminal::{Event, KeyCode, KeyEvent, Size},
+        testing::TestBackend,
+        Bindings, Component, ComponentExt, ComponentLink, Layout, Rect, ShouldRender,
+    };
+
+    /// Sets a flag when dropped, i.e. when the task holding it is cancelled.
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Completes once the flag is set.
+    struct Ready(Arc<AtomicBool>);
+
+    impl Future for Ready {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, _context: &mut Context<'_>) -> Poll<()> {
+            if self.0.load(Ordering::SeqCst) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        }
+    }
+
+    enum LoaderMessage {
+        Loaded(usize),
+        Computed(&'static str),
+    }
+
+    /// Spawns two tasks which complete and one which never does.
+    struct Loader {
+        loaded: usize,
+        computed: &'static str,
+    }
+
+    impl Component for Loader {
+        type Message = LoaderMessage;
+        type Properties = Arc<AtomicBool>;
+
+        fn create(dropped: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            link.spawn(async { LoaderMessage::Loaded(21 * 2) });
+            link.spawn_blocking(|| LoaderMessage::Computed("blocking"));
+            let flag = DropFlag(dropped);
+            link.spawn(async move {
+                let _flag = flag;
+                std::future::pending().await
+            });
+            Self {
+                loaded: 0,
+                computed: "",
+            }
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                LoaderMessage::Loaded(loaded) => self.loaded = loaded,
+                LoaderMessage::Computed(computed) => self.computed = computed,
+            }
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(format!("{} {}", self.loaded, self.computed)))
+        }
+    }
+
+    /// Spawns a task which completes once the flag is set, if given one.
+    struct Delayed {
+        loaded: usize,
+    }
+
+    impl Component for Delayed {
+        type Message = usize;
+        type Properties = Option<Arc<AtomicBool>>;
+
+        fn create(ready: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            if let Some(ready) = ready {
+                link.spawn(async move {
+                    Ready(ready).await;
+                    42
+                });
+            }
+            Self { loaded: 0 }
+        }
+
+        fn update(&mut self, loaded: Self::Message) -> ShouldRender {
+            self.loaded = loaded;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(self.loaded.to_string()))
+        }
+    }
+
+    /// Shows the layout returned by its properties, hidden or shown again
+    /// when `h` is pressed.
+    struct Toggle {
+        content: Rc<dyn Fn() -> Layout>,
+        shown: bool,
+    }
+
+    impl Component for Toggle {
+        type Message = ();
+        type Properties = Rc<dyn Fn() -> Layout>;
+
+        fn create(content: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self {
+                content,
+                shown: true,
+            }
+        }
+
+        fn update(&mut self, _message: Self::Message) -> ShouldRender {
+            self.shown = !self.shown;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            if self.shown {
+                (self.content)()
+            } else {
+                Text::with(TextProperties::new().content("hidden"))
+            }
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("toggle", [KeyEvent::from(KeyCode::Char('h'))], || ());
+        }
+    }
+
+    #[test]
+    fn spawned_tasks_deliver_messages_and_are_cancelled_on_unmount() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let content = {
+            let dropped = dropped.clone();
+            Rc::new(move || Loader::with(dropped.clone()))
+        };
+        let mut app = TestBackend::new(Size::new(11, 1), Toggle::with(content));
+        app.assert_lines(&["42 blocking"]);
+        assert_eq!(app.running_tasks(), 1);
+        assert!(!dropped.load(Ordering::SeqCst));
+
+        // Unmounting the loader drops its pending task
+        app.key(KeyCode::Char('h'));
+        app.assert_lines(&["hidden"]);
+        assert_eq!(app.running_tasks(), 0);
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn queued_results_are_not_delivered_to_a_remounted_component() {
+        let ready = Arc::new(AtomicBool::new(false));
+        // Only the first `Delayed` spawns a task
+        let content = {
+            let ready = RefCell::new(Some(ready.clone()));
+            Rc::new(move || Delayed::with(ready.borrow_mut().take()))
+        };
+        let mut app = TestBackend::new(Size::new(6, 1), Toggle::with(content));
+        app.assert_lines(&["0"]);
+
+        // The result is sent, but the component is unmounted and a new one is
+        // mounted with the same id before it is delivered
+        ready.store(true, Ordering::SeqCst);
+        assert!(app.poll_tasks());
+        for _ in 0..2 {
+            app.app()
+                .handle_input(Event::Key(KeyEvent::from(KeyCode::Char('h'))));
+            app.app().draw();
+        }
+        app.run_until_idle();
+        app.assert_lines(&["0"]);
+    }
+}
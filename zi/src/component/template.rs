@@ -7,9 +7,12 @@ use std::{
 use super::{
     bindings::{CommandId, DynamicBindings, NamedBindingQuery},
     layout::{ComponentKey, Layout},
-    Component, ComponentLink, MessageSender, ShouldRender,
+    Component, ComponentLink, ComponentRuntime, MessageSender, ShouldRender,
+};
+use crate::{
+    terminal::{MouseEvent, Rect, Size},
+    KeyEvent,
 };
-use crate::{terminal::Rect, KeyEvent};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct ComponentId {
@@ -104,6 +107,18 @@ pub(crate) trait Renderable {
         pressed: &[KeyEvent],
     ) -> Option<DynamicMessage>;
 
+    fn run_mouse_handler(
+        &self,
+        bindings: &DynamicBindings,
+        event: MouseEvent,
+    ) -> Option<DynamicMessage>;
+
+    fn run_paste_handler(&self, bindings: &DynamicBindings, text: &str) -> Option<DynamicMessage>;
+
+    fn focus_gained(&mut self) -> ShouldRender;
+
+    fn focus_lost(&mut self) -> ShouldRender;
+
     fn tick(&self) -> Option<DynamicMessage>;
 }
 
@@ -159,6 +174,30 @@ impl<ComponentT: Component> Renderable for ComponentT {
         bindings.execute_command(self, command_id, keys)
     }
 
+    #[inline]
+    fn run_mouse_handler(
+        &self,
+        bindings: &DynamicBindings,
+        event: MouseEvent,
+    ) -> Option<DynamicMessage> {
+        bindings.execute_mouse_handler(self, event)
+    }
+
+    #[inline]
+    fn run_paste_handler(&self, bindings: &DynamicBindings, text: &str) -> Option<DynamicMessage> {
+        bindings.execute_paste_handler(self, text)
+    }
+
+    #[inline]
+    fn focus_gained(&mut self) -> ShouldRender {
+        <Self as Component>::focus_gained(self)
+    }
+
+    #[inline]
+    fn focus_lost(&mut self) -> ShouldRender {
+        <Self as Component>::focus_lost(self)
+    }
+
     #[inline]
     fn tick(&self) -> Option<DynamicMessage> {
         <Self as Component>::tick(self).map(|message| DynamicMessage(Box::new(message)))
@@ -177,9 +216,12 @@ pub(crate) trait Template {
         id: ComponentId,
         frame: Rect,
         sender: Box<dyn MessageSender>,
+        runtime: ComponentRuntime,
     ) -> (Box<dyn Renderable + 'static>, DynamicBindings);
 
     fn dynamic_properties(&mut self) -> DynamicProperties;
+
+    fn size_hint(&self, available: Size) -> Option<Size>;
 }
 
 pub(crate) struct ComponentDef<ComponentT: Component> {
@@ -224,8 +266,9 @@ impl<ComponentT: Component> Template for ComponentDef<ComponentT> {
         component_id: ComponentId,
         frame: Rect,
         sender: Box<dyn MessageSender>,
+        runtime: ComponentRuntime,
     ) -> (Box<dyn Renderable>, DynamicBindings) {
-        let link = ComponentLink::new(sender, component_id);
+        let link = ComponentLink::new(sender, component_id, runtime);
         (
             Box::new(ComponentT::create(self.properties_unwrap(), frame, link)),
             DynamicBindings::new::<ComponentT>(),
@@ -236,4 +279,11 @@ impl<ComponentT: Component> Template for ComponentDef<ComponentT> {
     fn dynamic_properties(&mut self) -> DynamicProperties {
         DynamicProperties(Box::new(self.properties_unwrap()))
     }
+
+    #[inline]
+    fn size_hint(&self, available: Size) -> Option<Size> {
+        self.properties
+            .as_ref()
+            .and_then(|properties| ComponentT::size_hint(properties, available))
+    }
 }
@@ -6,23 +6,26 @@ use crate::{
 
 pub struct BorderProperties {
     pub component: Callback<(), Layout>,
-    pub style: Style,
+    /// Overrides the style from the theme, `border` or `border.focused`.
+    pub style: Option<Style>,
     pub stroke: BorderStroke,
     pub title: Option<(String, Style)>,
+    pub focused: bool,
 }
 
 impl BorderProperties {
     pub fn new(component: impl Fn() -> Layout + 'static) -> Self {
         Self {
             component: (move |_| component()).into(),
-            style: Style::default(),
+            style: None,
             stroke: BorderStroke::default(),
             title: None,
+            focused: false,
         }
     }
 
     pub fn style(mut self, style: impl Into<Style>) -> Self {
-        self.style = style.into();
+        self.style = Some(style.into());
         self
     }
 
@@ -35,19 +38,31 @@ impl BorderProperties {
         self.title = title.map(|title| (title.0.into(), title.1));
         self
     }
+
+    /// Draws the border in the `border.focused` style of the theme, e.g. to
+    /// highlight the focused one of several bordered components.
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
 }
 
 pub struct Border {
     properties: BorderProperties,
     frame: Rect,
+    link: ComponentLink<Self>,
 }
 
 impl Component for Border {
     type Message = ();
     type Properties = BorderProperties;
 
-    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
-        Self { properties, frame }
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            link,
+        }
     }
 
     fn change(&mut self, properties: Self::Properties) -> ShouldRender {
@@ -62,13 +77,21 @@ impl Component for Border {
 
     fn view(&self) -> Layout {
         let stroke = &self.properties.stroke;
+        let style = self.properties.style.unwrap_or_else(|| {
+            let token = if self.properties.focused {
+                "border.focused"
+            } else {
+                "border"
+            };
+            self.link.theme().style(token)
+        });
 
         // Draw the top border
         let mut top_border = Canvas::new(Size::new(self.frame.size.width, 1));
         top_border.draw_graphemes(
             0,
             0,
-            self.properties.style,
+            style,
             iter::once(stroke.top_left_corner)
                 .chain(
                     iter::repeat(stroke.top_horizontal)
@@ -85,12 +108,7 @@ impl Component for Border {
         // Draw right border
         let mut right_border = Canvas::new(Size::new(1, self.frame.size.height.saturating_sub(2)));
         (0..self.frame.size.height.saturating_sub(2)).for_each(|y| {
-            right_border.draw_graphemes(
-                0,
-                y,
-                self.properties.style,
-                iter::once(stroke.right_vertical),
-            );
+            right_border.draw_graphemes(0, y, style, iter::once(stroke.right_vertical));
         });
 
         // Draw bottom border
@@ -98,7 +116,7 @@ impl Component for Border {
         bottom_border.draw_graphemes(
             0,
             0,
-            self.properties.style,
+            style,
             iter::once(stroke.bottom_left_corner)
                 .chain(
                     iter::repeat(stroke.bottom_horizontal)
@@ -110,12 +128,7 @@ impl Component for Border {
         // Draw left border
         let mut left_border = Canvas::new(Size::new(1, self.frame.size.height.saturating_sub(2)));
         (0..self.frame.size.height.saturating_sub(2)).for_each(|y| {
-            left_border.draw_graphemes(
-                0,
-                y,
-                self.properties.style,
-                iter::once(stroke.left_vertical),
-            );
+            left_border.draw_graphemes(0, y, style, iter::once(stroke.left_vertical));
         });
 
         // Assemble layout
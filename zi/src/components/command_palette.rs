@@ -0,0 +1,458 @@
+//! A searchable list of the commands reachable from the focused components.
+//!
+//! `CommandPalette` combines an [`Input`](../input/struct.Input.html) with a
+//! [`Select`](../select/struct.Select.html). When created, it requests the
+//! commands that a key press could currently trigger (leaving out its own and
+//! those bound only to `AnyCharacter`, which can't be run without a key) and
+//! lists them together with their first key binding. Typing filters the list
+//! to commands whose name contains every word of the query, `Enter` runs the
+//! selected command and `Esc` or `C-g` cancel. In both cases `on_close` is
+//! called, typically to unmount the palette.
+
+use ropey::Rope;
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    input::{Cursor, Input, InputChange, InputProperties, InputStyle},
+    select::{Select, SelectProperties},
+    text::{Text, TextAlign, TextProperties},
+};
+use crate::{
+    app::CommandInfo,
+    component::bindings::{KeyPattern, KeySequenceSlice},
+    Bindings, Callback, Component, ComponentExt, ComponentLink, FlexDirection, Item, KeyCode,
+    KeyEvent, KeyModifiers, Layout, Rect, ShouldRender, Style,
+};
+
+/// Styles left as `None` are looked up in the theme, see the
+/// [`theme`](../../theme/index.html) module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandPaletteProperties {
+    /// Overrides `select.item` from the theme.
+    pub style: Option<Style>,
+    /// Overrides `select.selected` from the theme.
+    pub selected_style: Option<Style>,
+    /// Overrides `command-palette.key` from the theme.
+    pub key_style: Option<Style>,
+    /// Overrides `input.content` and `input.cursor` from the theme.
+    pub input_style: Option<InputStyle>,
+    /// Whether the palette is focused. When `None`, the palette is part of
+    /// the focus chain managed by the runtime instead. Defaults to
+    /// `Some(true)`, so opening the palette doesn't move focus away from the
+    /// components whose commands it lists.
+    pub focused: Option<bool>,
+    pub on_close: Option<Callback<()>>,
+}
+
+impl CommandPaletteProperties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn selected_style(mut self, selected_style: Style) -> Self {
+        self.selected_style = Some(selected_style);
+        self
+    }
+
+    pub fn key_style(mut self, key_style: Style) -> Self {
+        self.key_style = Some(key_style);
+        self
+    }
+
+    pub fn input_style(mut self, input_style: InputStyle) -> Self {
+        self.input_style = Some(input_style);
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = Some(focused);
+        self
+    }
+
+    pub fn on_close(mut self, on_close: impl Into<Callback<()>>) -> Self {
+        self.on_close = Some(on_close.into());
+        self
+    }
+}
+
+impl Default for CommandPaletteProperties {
+    fn default() -> Self {
+        Self {
+            style: None,
+            selected_style: None,
+            key_style: None,
+            input_style: None,
+            focused: Some(true),
+            on_close: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Message {
+    SetCommands(Vec<CommandInfo>),
+    InputChange(InputChange),
+    SelectItem(usize),
+    RunSelected,
+    Close,
+}
+
+/// A command listed by the palette.
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    name: Cow<'static, str>,
+    keys: String,
+}
+
+pub struct CommandPalette {
+    properties: CommandPaletteProperties,
+    link: ComponentLink<Self>,
+    entries: Vec<Entry>,
+    matches: Vec<usize>,
+    query: Rope,
+    cursor: Cursor,
+    selected: usize,
+    has_focus: bool,
+}
+
+impl CommandPalette {
+    fn update_matches(&mut self) {
+        let query = self.query.to_string().to_lowercase();
+        let words: Vec<_> = query.split_whitespace().collect();
+        let entries = &self.entries;
+        self.matches = (0..entries.len())
+            .filter(|&index| {
+                let name = entries[index].name.to_lowercase();
+                words.iter().all(|word| name.contains(word))
+            })
+            .collect();
+        self.selected = 0;
+    }
+
+    fn close(&self) {
+        if let Some(on_close) = self.properties.on_close.as_ref() {
+            on_close.emit(());
+        }
+    }
+}
+
+impl Component for CommandPalette {
+    type Message = Message;
+    type Properties = CommandPaletteProperties;
+
+    fn create(properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+        link.request_commands(Message::SetCommands);
+        Self {
+            properties,
+            link,
+            entries: Vec::new(),
+            matches: Vec::new(),
+            query: "\n".into(),
+            cursor: Cursor::new(),
+            selected: 0,
+            has_focus: false,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            self.properties = properties;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::SetCommands(commands) => {
+                // Only the innermost command with a given name can be run
+                self.entries.clear();
+                for command in commands
+                    .into_iter()
+                    .filter(|command| command.reachable && command.runnable_by_name())
+                {
+                    if self.entries.iter().any(|entry| entry.name == command.name) {
+                        continue;
+                    }
+                    let keys = command
+                        .keys
+                        .iter()
+                        .find_map(|pattern| match pattern {
+                            KeyPattern::Keys(keys) => {
+                                Some(KeySequenceSlice::from(keys.as_slice()).to_string())
+                            }
+                            KeyPattern::EndsWith(keys) => {
+                                Some(KeySequenceSlice::from(&keys[..]).to_string())
+                            }
+                            KeyPattern::AnyCharacter => None,
+                        })
+                        .unwrap_or_default();
+                    self.entries.push(Entry {
+                        name: command.name,
+                        keys,
+                    });
+                }
+                self.update_matches();
+            }
+            Message::InputChange(InputChange { content, cursor }) => {
+                self.cursor = cursor;
+                if let Some(content) = content {
+                    self.query = content;
+                    self.update_matches();
+                }
+            }
+            Message::SelectItem(selected) => {
+                self.selected = selected;
+            }
+            Message::RunSelected => {
+                let entry = match self.matches.get(self.selected) {
+                    Some(&index) => &self.entries[index],
+                    None => return ShouldRender::No,
+                };
+                self.link.run_command_outside(entry.name.clone());
+                self.close();
+            }
+            Message::Close => self.close(),
+        }
+        ShouldRender::Yes
+    }
+
+    fn focus_gained(&mut self) -> ShouldRender {
+        self.has_focus = true;
+        ShouldRender::Yes
+    }
+
+    fn focus_lost(&mut self) -> ShouldRender {
+        self.has_focus = false;
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let Self {
+            properties:
+                CommandPaletteProperties {
+                    style,
+                    selected_style,
+                    key_style,
+                    ref input_style,
+                    focused,
+                    ..
+                },
+            ref link,
+            ref entries,
+            ref matches,
+            ref query,
+            ref cursor,
+            selected,
+            has_focus,
+        } = *self;
+        // The input and the list are focused along with the palette
+        let focused = Some(focused.unwrap_or(has_focus));
+        let theme = link.theme();
+        let style = style.unwrap_or_else(|| theme.style("select.item"));
+        let selected_style = selected_style.unwrap_or_else(|| theme.style("select.selected"));
+        let key_style = key_style.unwrap_or_else(|| theme.style("command-palette.key"));
+
+        let input = Input::with(InputProperties {
+            style: input_style.clone(),
+            content: query.clone(),
+            cursor: cursor.clone(),
+            on_change: Some(link.callback(Message::InputChange)),
+            focused,
+        });
+
+        let keys_width = matches
+            .iter()
+            .map(|&index| entries[index].keys.width())
+            .max()
+            .unwrap_or(0);
+        let matched: Vec<_> = matches
+            .iter()
+            .map(|&index| entries[index].clone())
+            .collect();
+        let select = Select::with(SelectProperties {
+            background: Some(style),
+            direction: FlexDirection::Column,
+            focused,
+            item_at: (move |index| {
+                let Entry { ref name, ref keys } = matched[index];
+                let (style, key_style) = if index == selected {
+                    (selected_style, selected_style)
+                } else {
+                    (style, key_style)
+                };
+                Item::fixed(1)(Layout::row([
+                    Item::auto(Text::with(
+                        TextProperties::new().style(style).content(name.as_ref()),
+                    )),
+                    Item::fixed(keys_width)(Text::with(
+                        TextProperties::new()
+                            .style(key_style)
+                            .align(TextAlign::Right)
+                            .content(keys.as_str()),
+                    )),
+                ]))
+            })
+            .into(),
+            num_items: matches.len(),
+            item_size: 1,
+            selected,
+            on_change: Some(link.callback(Message::SelectItem)),
+        });
+
+        Layout::column([Item::fixed(1)(input), Item::auto(select)])
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused.unwrap_or(false));
+        bindings.set_focusable(self.properties.focused.is_none());
+        if !bindings.is_empty() {
+            return;
+        }
+
+        bindings.add("run-command", [KeyEvent::from(KeyCode::Enter)], || {
+            Message::RunSelected
+        });
+        bindings
+            .command("cancel", || Message::Close)
+            .with([KeyEvent::from(KeyCode::Esc)])
+            .with([KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL)]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{terminal::Size, testing::TestBackend, AnyCharacter};
+
+    struct Workspace {
+        link: ComponentLink<Self>,
+        palette_open: bool,
+        status: &'static str,
+    }
+
+    #[derive(Debug)]
+    enum WorkspaceMessage {
+        OpenPalette,
+        ClosePalette,
+        Status(&'static str),
+    }
+
+    impl Component for Workspace {
+        type Message = WorkspaceMessage;
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self {
+                link,
+                palette_open: false,
+                status: "",
+            }
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                WorkspaceMessage::OpenPalette => self.palette_open = true,
+                WorkspaceMessage::ClosePalette => self.palette_open = false,
+                WorkspaceMessage::Status(status) => self.status = status,
+            }
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            let status = Item::fixed(1)(Text::with(TextProperties::new().content(self.status)));
+            if self.palette_open {
+                let style = Style::default();
+                Layout::column([
+                    status,
+                    Item::auto(CommandPalette::with(
+                        CommandPaletteProperties::new()
+                            .style(style)
+                            .key_style(style)
+                            .selected_style(style.invert())
+                            .on_close(self.link.callback(|_| WorkspaceMessage::ClosePalette)),
+                    )),
+                ])
+            } else {
+                Layout::column([status, Item::auto(Text::with(TextProperties::new()))])
+            }
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            let ctrl = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::CONTROL);
+            bindings.add(
+                "command-palette",
+                [KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT)],
+                || WorkspaceMessage::OpenPalette,
+            );
+            bindings.add("save-buffer", [ctrl('x'), ctrl('s')], || {
+                WorkspaceMessage::Status("saved")
+            });
+            bindings.add(
+                "kill-buffer",
+                [ctrl('x'), KeyCode::Char('k').into()],
+                || WorkspaceMessage::Status("killed"),
+            );
+            // Not listed, as it needs the typed key
+            bindings.add(
+                "self-insert",
+                AnyCharacter,
+                |keys: &[KeyEvent]| match keys {
+                    [KeyEvent {
+                        code: KeyCode::Char(_),
+                        modifiers,
+                    }] if modifiers.is_empty() => Some(WorkspaceMessage::Status("typed")),
+                    _ => None,
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn command_palette_filters_and_runs_commands() {
+        let mut app = TestBackend::new(Size::new(24, 6), Workspace::with(()));
+        app.key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT));
+        app.assert_lines(&[
+            "",
+            "",
+            "command-palette      A-x",
+            "save-buffer      C-x C-s",
+            "kill-buffer        C-x k",
+            "",
+        ]);
+        assert_eq!(app.style(0, 2), Some(Style::default().invert()));
+
+        // Typing filters the list, the selection moves with `C-n`
+        app.keys([KeyCode::Char('b'), KeyCode::Char('u')]);
+        app.assert_lines(&[
+            "",
+            "bu",
+            "save-buffer      C-x C-s",
+            "kill-buffer        C-x k",
+            "",
+            "",
+        ]);
+        app.key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL));
+        assert_eq!(app.style(0, 3), Some(Style::default().invert()));
+
+        // `Enter` runs the selected command and closes the palette
+        app.key(KeyCode::Enter);
+        app.assert_lines(&["killed", "", "", "", "", ""]);
+
+        // So does `Esc`, without running anything
+        app.key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT))
+            .key(KeyCode::Esc);
+        app.assert_lines(&["killed", "", "", "", "", ""]);
+    }
+}
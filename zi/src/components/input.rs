@@ -1,22 +1,27 @@
 use ropey::Rope;
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::{
     layout::Layout,
     text::{cursor, CharIndex, TextStorage},
-    AnyCharacter, Bindings, Callback, Canvas, Colour, Component, ComponentLink, KeyCode, KeyEvent,
-    KeyModifiers, Rect, ShouldRender, Style,
+    AnyCharacter, Bindings, Callback, Canvas, Component, ComponentLink, KeyCode, KeyEvent,
+    KeyModifiers, Rect, ShouldRender, Style, Theme,
 };
 
 pub use crate::text::Cursor;
 
 #[derive(Clone, PartialEq)]
 pub struct InputProperties {
-    pub style: InputStyle,
+    /// Overrides the styles from the theme, `input.content` and
+    /// `input.cursor`.
+    pub style: Option<InputStyle>,
     pub content: Rope,
     pub cursor: Cursor,
     pub on_change: Option<Callback<InputChange>>,
-    pub focused: bool,
+    /// Whether the input is focused. When `None`, the input is part of the
+    /// focus chain managed by the runtime instead.
+    pub focused: Option<bool>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -25,19 +30,22 @@ pub struct InputStyle {
     pub cursor: Style,
 }
 
-impl Default for InputStyle {
-    fn default() -> Self {
-        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
-        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
-        const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
-
+impl InputStyle {
+    /// Looks up the styles of the `input.content` and `input.cursor` tokens.
+    pub fn from_theme(theme: &Theme) -> Self {
         Self {
-            content: Style::normal(DARK0_SOFT, LIGHT2),
-            cursor: Style::normal(BRIGHT_BLUE, DARK0_SOFT),
+            content: theme.style("input.content"),
+            cursor: theme.style("input.cursor"),
         }
     }
 }
 
+impl Default for InputStyle {
+    fn default() -> Self {
+        Self::from_theme(&Theme::dark())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct InputChange {
     pub content: Option<Rope>,
@@ -47,16 +55,21 @@ pub struct InputChange {
 pub struct Input {
     properties: InputProperties,
     frame: Rect,
+    link: ComponentLink<Self>,
 }
 
 impl Component for Input {
     type Message = Message;
     type Properties = InputProperties;
 
-    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
         let mut content = properties.content.clone();
         cursor::ensure_trailing_newline_with_content(&mut content);
-        Self { properties, frame }
+        Self {
+            properties,
+            frame,
+            link,
+        }
     }
 
     fn change(&mut self, properties: Self::Properties) -> ShouldRender {
@@ -95,6 +108,12 @@ impl Component for Input {
                 cursor.move_right(&new_content);
                 content_change = Some(new_content);
             }
+            Message::InsertStr(text) => {
+                let mut new_content = self.properties.content.clone();
+                cursor.insert_str(&mut new_content, &text);
+                cursor.move_right_n(&new_content, text.graphemes(true).count());
+                content_change = Some(new_content);
+            }
             Message::DeleteBackward => {
                 let mut new_content = self.properties.content.clone();
                 cursor.backspace(&mut new_content);
@@ -126,8 +145,12 @@ impl Component for Input {
                     ref style,
                     ..
                 },
+            ref link,
             ..
         } = *self;
+        let style = style
+            .clone()
+            .unwrap_or_else(|| InputStyle::from_theme(&link.theme()));
 
         let mut canvas = Canvas::new(self.frame.size);
         canvas.clear(style.content);
@@ -158,7 +181,8 @@ impl Component for Input {
     }
 
     fn bindings(&self, bindings: &mut Bindings<Self>) {
-        bindings.set_focus(self.properties.focused);
+        bindings.set_focus(self.properties.focused.unwrap_or(false));
+        bindings.set_focusable(self.properties.focused.is_none());
         if !bindings.is_empty() {
             return;
         }
@@ -188,19 +212,44 @@ impl Component for Input {
             [KeyEvent::from(KeyCode::Backspace)],
             || Message::DeleteBackward,
         );
+        // Chords with either `Ctrl` or `Alt` bubble up to the ancestors, while
+        // `Ctrl` and `Alt` together is how AltGr is reported on some platforms
         bindings.add(
             "insert-character",
             AnyCharacter,
             |keys: &[KeyEvent]| match keys {
                 &[KeyEvent {
                     code: KeyCode::Char(character),
-                    modifiers: _,
-                }] if character != '\n' && character != '\r' && character != '\t' => {
+                    modifiers,
+                }] if character != '\n'
+                    && character != '\r'
+                    && character != '\t'
+                    && modifiers.contains(KeyModifiers::CONTROL)
+                        == modifiers.contains(KeyModifiers::ALT) =>
+                {
                     Some(Message::InsertChar(character))
                 }
                 _ => None,
             },
         );
+        // The input is a single line, so each line break or tab in pasted text
+        // becomes a space and other control characters are stripped
+        bindings.paste(|text: &str| {
+            let text: String = text
+                .replace("\r\n", "\n")
+                .chars()
+                .filter_map(|character| match character {
+                    '\n' | '\r' | '\t' => Some(' '),
+                    character if character.is_control() => None,
+                    character => Some(character),
+                })
+                .collect();
+            if text.is_empty() {
+                None
+            } else {
+                Some(Message::InsertStr(text))
+            }
+        });
     }
 }
 
@@ -209,8 +258,115 @@ pub enum Message {
     CursorLeft,
     CursorRight,
     InsertChar(char),
+    InsertStr(String),
     DeleteBackward,
     DeleteForward,
     StartOfLine,
     EndOfLine,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{
+        terminal::{Event, Size},
+        testing::TestBackend,
+        ComponentExt,
+    };
+
+    #[test]
+    fn focused_input_receives_the_whole_paste() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let on_change = {
+            let changes = changes.clone();
+            move |change: InputChange| changes.borrow_mut().push(change)
+        };
+        let mut app = TestBackend::new(
+            Size::new(10, 1),
+            Input::with(InputProperties {
+                style: None,
+                content: "ab\n".into(),
+                cursor: Cursor::new(),
+                on_change: Some(on_change.into()),
+                focused: Some(true),
+            }),
+        );
+
+        // Inserted in a single edit, with line breaks and tabs as spaces
+        app.input(Event::Paste("x\r\ny".into()))
+            .input(Event::Paste("foo\nbar\tbaz\u{7}".into()));
+        let changes = changes.borrow();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes[0].content.as_ref().map(Rope::to_string).as_deref(),
+            Some("x yab\n")
+        );
+        assert_eq!(
+            changes[1].content.as_ref().map(Rope::to_string).as_deref(),
+            Some("foo bar bazab\n")
+        );
+    }
+
+    #[test]
+    fn input_without_explicit_focus_is_focused_by_the_runtime() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let on_change = {
+            let changes = changes.clone();
+            move |change: InputChange| changes.borrow_mut().push(change)
+        };
+        let mut app = TestBackend::new(
+            Size::new(10, 1),
+            Input::with(InputProperties {
+                style: None,
+                content: "\n".into(),
+                cursor: Cursor::new(),
+                on_change: Some(on_change.into()),
+                focused: None,
+            }),
+        );
+
+        app.input(Event::Key(KeyEvent::from(KeyCode::Char('a'))));
+        let changes = changes.borrow();
+        assert_eq!(
+            changes[0].content.as_ref().map(Rope::to_string).as_deref(),
+            Some("a\n")
+        );
+    }
+
+    #[test]
+    fn characters_typed_with_ctrl_or_alt_alone_are_not_inserted() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let on_change = {
+            let changes = changes.clone();
+            move |change: InputChange| changes.borrow_mut().push(change)
+        };
+        let mut app = TestBackend::new(
+            Size::new(10, 1),
+            Input::with(InputProperties {
+                style: None,
+                content: "\n".into(),
+                cursor: Cursor::new(),
+                on_change: Some(on_change.into()),
+                focused: Some(true),
+            }),
+        );
+
+        app.key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL))
+            .key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT));
+        assert!(changes.borrow().is_empty());
+
+        // AltGr
+        app.key(KeyEvent::new(
+            KeyCode::Char('@'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        ));
+        let changes = changes.borrow();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].content.as_ref().map(Rope::to_string).as_deref(),
+            Some("@\n")
+        );
+    }
+}
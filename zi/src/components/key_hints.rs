@@ -0,0 +1,237 @@
+//! A "which-key" style popup listing the commands a pending key sequence can
+//! complete to.
+//!
+//! `KeyHints` listens to all key presses using
+//! [`Bindings::set_notify`](../../struct.Bindings.html#method.set_notify).
+//! While a prefix of a key binding is pending (e.g. after pressing `C-x` when
+//! `C-x C-f` is bound), it lists each possible next key together with the
+//! name of its command. The hints are cleared once the key sequence completes
+//! or is cancelled. Hints are drawn in columns and any that don't fit the
+//! frame are left out.
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    component::bindings::{Continuation, KeySequenceSlice, NamedBindingQuery},
+    Bindings, Canvas, Component, ComponentLink, KeyEvent, Layout, Rect, ShouldRender, Style,
+};
+
+/// The separator drawn between the keys and the command name of a hint.
+const SEPARATOR: &str = " → ";
+
+/// The number of blank columns between columns of hints.
+const COLUMN_GAP: usize = 2;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyHintsProperties {
+    /// Overrides the style from the theme, `key-hints`.
+    pub style: Option<Style>,
+    /// Overrides the style from the theme, `key-hints.key`.
+    pub key_style: Option<Style>,
+}
+
+impl KeyHintsProperties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn key_style(mut self, key_style: Style) -> Self {
+        self.key_style = Some(key_style);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Show(Vec<Continuation>),
+    Hide,
+}
+
+pub struct KeyHints {
+    properties: KeyHintsProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    hints: Option<Vec<Continuation>>,
+}
+
+impl Component for KeyHints {
+    type Message = Message;
+    type Properties = KeyHintsProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            link,
+            hints: None,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            self.properties = properties;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        let hints = match message {
+            Message::Show(continuations) => Some(continuations),
+            Message::Hide => None,
+        };
+        if self.hints != hints {
+            self.hints = hints;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn view(&self) -> Layout {
+        let Self {
+            frame,
+            properties: KeyHintsProperties { style, key_style },
+            ref link,
+            ref hints,
+        } = *self;
+        let theme = link.theme();
+        let style = style.unwrap_or_else(|| theme.style("key-hints"));
+        let key_style = key_style.unwrap_or_else(|| theme.style("key-hints.key"));
+
+        let mut canvas = Canvas::new(frame.size);
+        canvas.clear(style);
+        let (hints, num_rows) = match hints {
+            Some(hints) if frame.size.height > 0 => (hints, frame.size.height),
+            _ => return canvas.into(),
+        };
+
+        let hints: Vec<_> = hints
+            .iter()
+            .map(|continuation| {
+                (
+                    KeySequenceSlice::from(continuation.keys.as_slice()).to_string(),
+                    continuation.command.as_ref(),
+                )
+            })
+            .collect();
+        let keys_width = hints
+            .iter()
+            .map(|(keys, _)| keys.width())
+            .max()
+            .unwrap_or(0);
+        let command_width = hints
+            .iter()
+            .map(|(_, command)| command.width())
+            .max()
+            .unwrap_or(0);
+        let column_width = keys_width + SEPARATOR.width() + command_width + COLUMN_GAP;
+
+        for (index, (keys, command)) in hints.iter().enumerate() {
+            let x = (index / num_rows) * column_width;
+            let y = index % num_rows;
+            if x >= frame.size.width {
+                break;
+            }
+            canvas.draw_str(x, y, key_style, keys);
+            canvas.draw_str(x + keys_width, y, style, SEPARATOR);
+            canvas.draw_str(x + keys_width + SEPARATOR.width(), y, style, command);
+        }
+
+        canvas.into()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_notify(true);
+    }
+
+    fn notify_binding_queries(&self, queries: &[Option<NamedBindingQuery>], _keys: &[KeyEvent]) {
+        let pending = queries.iter().flatten().find_map(|query| match query {
+            NamedBindingQuery::PrefixOf(continuations) => Some(continuations),
+            NamedBindingQuery::Match(_) => None,
+        });
+        match pending {
+            Some(continuations) if self.hints.as_ref() != Some(continuations) => {
+                self.link.send(Message::Show(continuations.clone()))
+            }
+            None if self.hints.is_some() => self.link.send(Message::Hide),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::text::{Text, TextProperties},
+        terminal::{KeyCode, KeyModifiers, Size},
+        testing::TestBackend,
+        ComponentExt, Item,
+    };
+
+    struct Editor;
+
+    impl Component for Editor {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content("editor"))
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            let ctrl = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::CONTROL);
+            bindings.add("find-file", [ctrl('x'), ctrl('f')], || ());
+            bindings.add("save-buffer", [ctrl('x'), ctrl('s')], || ());
+            bindings.add("kill-buffer", [ctrl('x'), KeyCode::Char('k').into()], || ());
+        }
+    }
+
+    #[test]
+    fn key_hints_list_continuations_of_pending_prefix() {
+        let mut app = TestBackend::new(
+            Size::new(40, 3),
+            Layout::column([
+                Item::auto(Editor::with(())),
+                Item::fixed(2)(KeyHints::with(KeyHintsProperties::new())),
+            ]),
+        );
+        app.assert_lines(&["editor", "", ""]);
+
+        let ctrl_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        app.key(ctrl_x);
+        app.assert_lines(&[
+            "editor",
+            "C-f → find-file    k   → kill-buffer",
+            "C-s → save-buffer",
+        ]);
+
+        // Completing the sequence hides the hints
+        app.key(KeyCode::Char('k'));
+        app.assert_lines(&["editor", "", ""]);
+
+        // So does cancelling it with an unbound key
+        app.key(ctrl_x).key(KeyCode::Esc);
+        app.assert_lines(&["editor", "", ""]);
+    }
+}
@@ -1,6 +1,9 @@
 //! A collection of reusable components useful as building blocks.
 
 pub mod border;
+pub mod command_palette;
 pub mod input;
+pub mod key_hints;
+pub mod scroll_view;
 pub mod select;
 pub mod text;
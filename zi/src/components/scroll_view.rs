@@ -0,0 +1,354 @@
+//! A scrollable view of a layout larger than its frame.
+//!
+//! `ScrollView` lays out its content at a virtual size using a
+//! [`Viewport`](../../layout/struct.Viewport.html) and shows the part of it
+//! at the current scroll offset. When focused, the arrow keys scroll by one
+//! line or column, `PageUp` / `PageDown` by a page and `Home` / `End` go to
+//! the top and bottom. The mouse wheel scrolls too.
+//!
+//! The offset can be set from the parent with `scroll_to`, and `reveal` keeps
+//! a region of the content in view, e.g. the focused field of a long form.
+//! Both are applied when they change. `on_scroll` is called whenever the user
+//! scrolls.
+
+use std::cmp;
+
+use crate::{
+    Bindings, Callback, Canvas, Component, ComponentLink, Item, KeyCode, KeyEvent, Layout,
+    MouseEvent, MouseEventKind, Position, Rect, ShouldRender, Size, Style, Viewport,
+};
+
+/// The number of lines scrolled by one step of the mouse wheel.
+const WHEEL_LINES: usize = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollViewProperties {
+    pub content: Callback<(), Layout>,
+    pub content_size: Size,
+    pub scroll_to: Option<Position>,
+    pub reveal: Option<Rect>,
+    pub scrollbar: bool,
+    /// Overrides the style from the theme, `scroll-view.scrollbar`.
+    pub scrollbar_style: Option<Style>,
+    /// Whether the view is focused. When `None`, the view is part of the
+    /// focus chain managed by the runtime instead.
+    pub focused: Option<bool>,
+    pub on_scroll: Option<Callback<Position>>,
+}
+
+impl ScrollViewProperties {
+    /// Creates properties for content of the given size, laid out by the
+    /// `content` callback.
+    pub fn new(content_size: Size, content: impl Into<Callback<(), Layout>>) -> Self {
+        Self {
+            content: content.into(),
+            content_size,
+            scroll_to: None,
+            reveal: None,
+            scrollbar: true,
+            scrollbar_style: None,
+            focused: None,
+            on_scroll: None,
+        }
+    }
+
+    pub fn scroll_to(mut self, offset: Position) -> Self {
+        self.scroll_to = Some(offset);
+        self
+    }
+
+    pub fn reveal(mut self, region: Rect) -> Self {
+        self.reveal = Some(region);
+        self
+    }
+
+    pub fn scrollbar(mut self, scrollbar: bool) -> Self {
+        self.scrollbar = scrollbar;
+        self
+    }
+
+    pub fn scrollbar_style(mut self, scrollbar_style: Style) -> Self {
+        self.scrollbar_style = Some(scrollbar_style);
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = Some(focused);
+        self
+    }
+
+    pub fn on_scroll(mut self, on_scroll: impl Into<Callback<Position>>) -> Self {
+        self.on_scroll = Some(on_scroll.into());
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    ScrollUp(usize),
+    ScrollDown(usize),
+    ScrollLeft(usize),
+    ScrollRight(usize),
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+}
+
+pub struct ScrollView {
+    properties: ScrollViewProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    offset: Position,
+}
+
+impl ScrollView {
+    /// Returns `true` if the vertical scrollbar is drawn, which takes up the
+    /// last column of the frame.
+    fn has_scrollbar(&self) -> bool {
+        self.properties.scrollbar && self.properties.content_size.height > self.frame.size.height
+    }
+
+    /// The size of the visible part of the content.
+    fn viewport_size(&self) -> Size {
+        let scrollbar_width = if self.has_scrollbar() { 1 } else { 0 };
+        Size::new(
+            self.frame.size.width.saturating_sub(scrollbar_width),
+            self.frame.size.height,
+        )
+    }
+
+    fn max_offset(&self) -> Position {
+        let content_size = self.properties.content_size;
+        let viewport_size = self.viewport_size();
+        Position::new(
+            content_size.width.saturating_sub(viewport_size.width),
+            content_size.height.saturating_sub(viewport_size.height),
+        )
+    }
+
+    /// Sets the offset, clamped to the content. Returns `true` if it changed.
+    fn set_offset(&mut self, offset: Position) -> bool {
+        let max_offset = self.max_offset();
+        let offset = Position::new(
+            cmp::min(offset.x, max_offset.x),
+            cmp::min(offset.y, max_offset.y),
+        );
+        let changed = offset != self.offset;
+        self.offset = offset;
+        changed
+    }
+
+    /// Scrolls as little as possible to show a region of the content. If the
+    /// region doesn't fit, its top left corner is shown.
+    fn reveal(&mut self, region: Rect) {
+        let viewport_size = self.viewport_size();
+        let along = |offset: usize, start: usize, size: usize, visible: usize| {
+            if start < offset || size > visible {
+                start
+            } else if start + size > offset + visible {
+                start + size - visible
+            } else {
+                offset
+            }
+        };
+        self.set_offset(Position::new(
+            along(
+                self.offset.x,
+                region.origin.x,
+                region.size.width,
+                viewport_size.width,
+            ),
+            along(
+                self.offset.y,
+                region.origin.y,
+                region.size.height,
+                viewport_size.height,
+            ),
+        ));
+    }
+
+    fn scrollbar(&self) -> Canvas {
+        let height = self.frame.size.height;
+        let content_height = self.properties.content_size.height;
+        let style = self
+            .properties
+            .scrollbar_style
+            .unwrap_or_else(|| self.link.theme().style("scroll-view.scrollbar"));
+
+        // The thumb is as tall, relative to the track, as the visible part of
+        // the content, relative to all of it
+        let thumb_size = cmp::max(1, height * height / cmp::max(content_height, 1));
+        let max_offset = self.max_offset().y;
+        let thumb_offset = (self.offset.y * (height - thumb_size))
+            .checked_div(max_offset)
+            .unwrap_or(0);
+
+        let mut canvas = Canvas::new(Size::new(1, height));
+        for y in 0..height {
+            let symbol = if (thumb_offset..thumb_offset + thumb_size).contains(&y) {
+                "█"
+            } else {
+                "│"
+            };
+            canvas.draw_str(0, y, style, symbol);
+        }
+        canvas
+    }
+}
+
+impl Component for ScrollView {
+    type Message = Message;
+    type Properties = ScrollViewProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let mut scroll_view = Self {
+            properties,
+            frame,
+            link,
+            offset: Position::new(0, 0),
+        };
+        if let Some(offset) = scroll_view.properties.scroll_to {
+            scroll_view.set_offset(offset);
+        }
+        if let Some(region) = scroll_view.properties.reveal {
+            scroll_view.reveal(region);
+        }
+        scroll_view
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties == properties {
+            return ShouldRender::No;
+        }
+        let scroll_to = properties
+            .scroll_to
+            .filter(|_| properties.scroll_to != self.properties.scroll_to);
+        let reveal = properties
+            .reveal
+            .filter(|_| properties.reveal != self.properties.reveal);
+        self.properties = properties;
+
+        self.set_offset(scroll_to.unwrap_or(self.offset));
+        if let Some(region) = reveal {
+            self.reveal(region);
+        }
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        self.set_offset(self.offset);
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        let Position { x, y, .. } = self.offset;
+        let page = cmp::max(self.viewport_size().height, 1);
+        let offset = match message {
+            Message::ScrollUp(lines) => Position::new(x, y.saturating_sub(lines)),
+            Message::ScrollDown(lines) => Position::new(x, y.saturating_add(lines)),
+            Message::ScrollLeft(columns) => Position::new(x.saturating_sub(columns), y),
+            Message::ScrollRight(columns) => Position::new(x.saturating_add(columns), y),
+            Message::PageUp => Position::new(x, y.saturating_sub(page)),
+            Message::PageDown => Position::new(x, y.saturating_add(page)),
+            Message::Top => Position::new(x, 0),
+            Message::Bottom => Position::new(x, usize::MAX),
+        };
+        if !self.set_offset(offset) {
+            return ShouldRender::No;
+        }
+        if let Some(on_scroll) = self.properties.on_scroll.as_ref() {
+            on_scroll.emit(self.offset);
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let viewport = Viewport::new(
+            self.properties.content_size,
+            self.properties.content.emit(()),
+        )
+        .offset(self.offset);
+
+        if self.has_scrollbar() {
+            Layout::row([Item::auto(viewport), Item::fixed(1)(self.scrollbar())])
+        } else {
+            viewport.into()
+        }
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused.unwrap_or(false));
+        bindings.set_focusable(self.properties.focused.is_none());
+        if !bindings.is_empty() {
+            return;
+        }
+
+        bindings.add("scroll-up", [KeyEvent::from(KeyCode::Up)], || {
+            Message::ScrollUp(1)
+        });
+        bindings.add("scroll-down", [KeyEvent::from(KeyCode::Down)], || {
+            Message::ScrollDown(1)
+        });
+        bindings.add("scroll-left", [KeyEvent::from(KeyCode::Left)], || {
+            Message::ScrollLeft(1)
+        });
+        bindings.add("scroll-right", [KeyEvent::from(KeyCode::Right)], || {
+            Message::ScrollRight(1)
+        });
+        bindings.add("previous-page", [KeyEvent::from(KeyCode::PageUp)], || {
+            Message::PageUp
+        });
+        bindings.add("next-page", [KeyEvent::from(KeyCode::PageDown)], || {
+            Message::PageDown
+        });
+        bindings.add("scroll-to-top", [KeyEvent::from(KeyCode::Home)], || {
+            Message::Top
+        });
+        bindings.add("scroll-to-bottom", [KeyEvent::from(KeyCode::End)], || {
+            Message::Bottom
+        });
+        bindings.mouse(|event: MouseEvent| match event.kind {
+            MouseEventKind::ScrollUp => Some(Message::ScrollUp(WHEEL_LINES)),
+            MouseEventKind::ScrollDown => Some(Message::ScrollDown(WHEEL_LINES)),
+            _ => None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::text::{Text, TextProperties},
+        testing::TestBackend,
+        ComponentExt,
+    };
+
+    #[test]
+    fn scroll_view_clips_and_scrolls_its_content() {
+        let content = || {
+            let lines: Vec<_> = (0..10).map(|line| format!("line{}", line)).collect();
+            Text::with(TextProperties::new().content(lines.join("\n")))
+        };
+        let properties =
+            || ScrollViewProperties::new(Size::new(5, 10), move |_| content()).focused(true);
+
+        let mut app = TestBackend::new(Size::new(6, 3), ScrollView::with(properties()));
+        app.assert_lines(&["line0█", "line1│", "line2│"]);
+        app.keys([KeyCode::Down, KeyCode::Down]);
+        app.assert_lines(&["line2█", "line3│", "line4│"]);
+        app.key(KeyCode::End);
+        app.assert_lines(&["line7│", "line8│", "line9█"]);
+        app.key(KeyCode::PageUp);
+        app.assert_lines(&["line4│", "line5█", "line6│"]);
+
+        // Scrolling past the content is clamped
+        let app = TestBackend::new(
+            Size::new(5, 3),
+            ScrollView::with(properties().scrollbar(false).scroll_to(Position::new(3, 8))),
+        );
+        app.assert_lines(&["line7", "line8", "line9"]);
+    }
+}
@@ -10,9 +10,12 @@ use crate::{
 
 #[derive(Clone, PartialEq)]
 pub struct SelectProperties {
-    pub background: Style,
+    /// Overrides the style from the theme, `select.background`.
+    pub background: Option<Style>,
     pub direction: FlexDirection,
-    pub focused: bool,
+    /// Whether the select is focused. When `None`, the select is part of the
+    /// focus chain managed by the runtime instead.
+    pub focused: Option<bool>,
     pub item_at: Callback<usize, Item>,
     pub num_items: usize,
     pub item_size: usize,
@@ -33,6 +36,7 @@ pub enum Message {
 pub struct Select {
     properties: SelectProperties,
     frame: Rect,
+    link: ComponentLink<Self>,
     offset: usize,
 }
 
@@ -55,10 +59,11 @@ impl Component for Select {
     type Message = Message;
     type Properties = SelectProperties;
 
-    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
         let mut select = Self {
             properties,
             frame,
+            link,
             offset: 0,
         };
         select.ensure_selected_item_in_view();
@@ -122,8 +127,12 @@ impl Component for Select {
 
         if self.properties.item_size * num_visible_items < self.frame.size.height {
             // "Filler" component for the unused space
+            let background = self
+                .properties
+                .background
+                .unwrap_or_else(|| self.link.theme().style("select.background"));
             let spacer = iter::once(Item::auto(Text::with(
-                TextProperties::new().style(self.properties.background),
+                TextProperties::new().style(background),
             )));
             Layout::container(self.properties.direction, items.chain(spacer))
         } else {
@@ -132,7 +141,8 @@ impl Component for Select {
     }
 
     fn bindings(&self, bindings: &mut Bindings<Self>) {
-        bindings.set_focus(self.properties.focused);
+        bindings.set_focus(self.properties.focused.unwrap_or(false));
+        bindings.set_focusable(self.properties.focused.is_none());
 
         if !bindings.is_empty() {
             return;
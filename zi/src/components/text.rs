@@ -1,3 +1,4 @@
+use std::cmp;
 use unicode_width::UnicodeWidthStr;
 
 use crate::{layout::Layout, Canvas, Component, ComponentLink, Rect, ShouldRender, Size, Style};
@@ -146,6 +147,16 @@ impl Component for Text {
 
         canvas.into()
     }
+
+    fn size_hint(properties: &Self::Properties, available: Size) -> Option<Size> {
+        let TextProperties {
+            ref content, wrap, ..
+        } = *properties;
+        Some(match wrap {
+            TextWrap::None => text_block_size(content),
+            TextWrap::Word => wrapped_text_block_size(content, available.width),
+        })
+    }
 }
 
 fn text_block_size(text: &str) -> Size {
@@ -153,3 +164,28 @@ fn text_block_size(text: &str) -> Size {
     let height = text.lines().count();
     Size::new(width, height)
 }
+
+/// The size of a text block when words are wrapped to fit `max_width`, as
+/// drawn by `Text::view`.
+fn wrapped_text_block_size(text: &str, max_width: usize) -> Size {
+    let mut width = 0;
+    let mut height = 0;
+    for line in text.lines() {
+        let mut cursor_x = 0;
+        for word in line.split_whitespace() {
+            let word_width = UnicodeWidthStr::width(word);
+            if cursor_x > 0 {
+                if cursor_x >= max_width || word_width > max_width.saturating_sub(cursor_x + 1) {
+                    height += 1;
+                    cursor_x = 0;
+                } else {
+                    cursor_x += 1;
+                }
+            }
+            cursor_x += word_width;
+            width = cmp::max(width, cmp::min(cursor_x, max_width));
+        }
+        height += 1;
+    }
+    Size::new(width, height)
+}
@@ -144,27 +144,42 @@
 
 pub mod app;
 pub mod components;
+pub mod macros;
+pub mod store;
 pub mod terminal;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod theme;
 
 pub use component::{
-    bindings::{AnyCharacter, BindingQuery, Bindings, EndsWith, Keymap, NamedBindingQuery},
-    layout::{self, ComponentExt, ComponentKey, Container, FlexBasis, FlexDirection, Item},
+    bindings::{
+        AnyCharacter, BindingQuery, Bindings, Continuation, EndsWith, Keymap, LayerBindings,
+        LayerId, MouseFn, NamedBindingQuery, PasteFn,
+    },
+    layout::{
+        self, Align, ComponentExt, ComponentKey, Container, Edges, FlexBasis, FlexDirection, Grid,
+        GridCell, Item, Justify, Layer, Placement, Stack, Track, Viewport,
+    },
+    overrides::KeymapOverrides,
     Callback, Component, ComponentLink, Layout, ShouldRender,
 };
 pub use terminal::{
-    Background, Canvas, Colour, Foreground, KeyCode, KeyEvent, KeyModifiers, Position, Rect, Size,
-    Style,
+    AnsiColour, Background, Canvas, Colour, ColourSupport, Foreground, KeyCode, KeyEvent,
+    KeyModifiers, MouseButton, MouseEvent, MouseEventKind, ParseKeyError, Position, Rect, Size,
+    Style, UnderlineStyle,
 };
+pub use theme::Theme;
 
 pub mod prelude {
     //! The Zi prelude.
     pub use super::{
-        AnyCharacter, Bindings, Component, ComponentExt, ComponentLink, Container, FlexBasis,
-        FlexDirection, Item, Layout, ShouldRender,
+        Align, AnyCharacter, Bindings, Component, ComponentExt, ComponentLink, Container, Edges,
+        FlexBasis, FlexDirection, Grid, GridCell, Item, Justify, Layer, Layout, ShouldRender,
+        Stack, Track, Viewport,
     };
     pub use super::{
-        Background, Canvas, Colour, Foreground, KeyCode, KeyEvent, KeyModifiers, Position, Rect,
-        Size, Style,
+        Background, Canvas, Colour, Foreground, KeyCode, KeyEvent, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind, Position, Rect, Size, Style, Theme,
     };
 }
 
@@ -0,0 +1,262 @@
+//! Keyboard macros recorded from and replayed through the key dispatch of an
+//! [`App`](../app/struct.App.html).
+//!
+//! Macros are opt-in, see
+//! [`App::set_macros_enabled`](../app/struct.App.html#method.set_macros_enabled).
+//! While a macro is being recorded, every key delivered to components is
+//! appended to it. Replayed keys are queued and dispatched one at a time, as
+//! if they were typed, with messages delivered and the application redrawn in
+//! between so bindings are up to date for each key.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::terminal::KeyEvent;
+
+/// Recorded keyboard macros, stored by name.
+///
+/// Names are arbitrary strings, so registers in the style of vim are simply
+/// macros with single character names.
+#[derive(Clone, Debug, Default)]
+pub struct Macros {
+    macros: HashMap<String, Vec<KeyEvent>>,
+    last: Option<String>,
+    recording: Option<Recording>,
+    queue: VecDeque<KeyEvent>,
+    replaying: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Recording {
+    name: String,
+    keys: Vec<KeyEvent>,
+    /// Index of the first key of the most recent key sequence.
+    sequence_start: usize,
+}
+
+impl Macros {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the keys of a macro.
+    pub fn get(&self, name: &str) -> Option<&[KeyEvent]> {
+        self.macros.get(name).map(Vec::as_slice)
+    }
+
+    /// Stores a macro, replacing any existing macro with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, keys: impl IntoIterator<Item = KeyEvent>) {
+        self.macros.insert(name.into(), keys.into_iter().collect());
+    }
+
+    /// Removes a macro, returning its keys.
+    pub fn remove(&mut self, name: &str) -> Option<Vec<KeyEvent>> {
+        if self.last.as_deref() == Some(name) {
+            self.last = None;
+        }
+        self.macros.remove(name)
+    }
+
+    /// Returns the names of all stored macros, in arbitrary order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.macros.keys().map(String::as_str)
+    }
+
+    /// Returns the name of the most recently recorded macro.
+    pub fn last(&self) -> Option<&str> {
+        self.last.as_deref()
+    }
+
+    /// Returns the name of the macro being recorded, if any.
+    pub fn recording(&self) -> Option<&str> {
+        self.recording
+            .as_ref()
+            .map(|recording| recording.name.as_str())
+    }
+
+    /// Starts recording a macro. A recording in progress is discarded.
+    pub(crate) fn start(&mut self, name: String) {
+        self.recording = Some(Recording {
+            name,
+            keys: Vec::new(),
+            sequence_start: 0,
+        });
+    }
+
+    /// Stops recording and stores the macro. If `drop_last_sequence` is set,
+    /// the keys of the last key sequence are left out, i.e. the binding that
+    /// stopped the recording. Returns `false` if no macro was being recorded.
+    pub(crate) fn stop(&mut self, drop_last_sequence: bool) -> bool {
+        let Recording {
+            name,
+            mut keys,
+            sequence_start,
+        } = match self.recording.take() {
+            Some(recording) => recording,
+            None => return false,
+        };
+        if drop_last_sequence {
+            keys.truncate(sequence_start);
+        }
+        self.macros.insert(name.clone(), keys);
+        self.last = Some(name);
+        true
+    }
+
+    /// Appends a key to the macro being recorded, unless the key is being
+    /// replayed.
+    pub(crate) fn record(&mut self, key: KeyEvent, starts_sequence: bool) {
+        if self.replaying {
+            return;
+        }
+        if let Some(recording) = self.recording.as_mut() {
+            if starts_sequence {
+                recording.sequence_start = recording.keys.len();
+            }
+            recording.keys.push(key);
+        }
+    }
+
+    /// Queues the keys of a macro `count` times. Returns `false` if there is
+    /// no such macro.
+    pub(crate) fn queue(&mut self, name: &str, count: usize) -> bool {
+        let keys = match self.macros.get(name) {
+            Some(keys) => keys,
+            None => return false,
+        };
+        for _ in 0..count {
+            self.queue.extend(keys.iter().copied());
+        }
+        self.replaying = true;
+        true
+    }
+
+    /// Returns `true` while queued keys are being replayed.
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.replaying
+    }
+
+    /// Takes the next queued key. Once the queue is exhausted, this returns
+    /// `None` and ends the replay.
+    pub(crate) fn next_queued(&mut self) -> Option<KeyEvent> {
+        let key = self.queue.pop_front();
+        if key.is_none() {
+            self.replaying = false;
+        }
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        components::text::{Text, TextProperties},
+        prelude::*,
+        testing::TestBackend,
+    };
+
+    struct Tally {
+        link: ComponentLink<Self>,
+        count: usize,
+    }
+
+    #[derive(Debug)]
+    enum TallyMessage {
+        Increment,
+        Double,
+        Doubled,
+        StartMacro,
+        StopMacro,
+        ReplayMacro(usize),
+    }
+
+    impl Component for Tally {
+        type Message = TallyMessage;
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self { link, count: 0 }
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                TallyMessage::Increment => self.count += 1,
+                // Doubling goes through a message, so a replayed key after it
+                // only sees the new count if messages are delivered in between
+                TallyMessage::Double => self.link.send(TallyMessage::Doubled),
+                TallyMessage::Doubled => self.count *= 2,
+                TallyMessage::StartMacro => self.link.start_macro("m"),
+                TallyMessage::StopMacro => self.link.stop_macro(),
+                TallyMessage::ReplayMacro(count) => self.link.replay_macro("m", count),
+            }
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(format!("count={}", self.count)))
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            let ctrl = |char| KeyEvent::new(KeyCode::Char(char), KeyModifiers::CONTROL);
+            bindings.add("increment", [KeyEvent::from(KeyCode::Char('+'))], || {
+                TallyMessage::Increment
+            });
+            bindings.add("double", [KeyEvent::from(KeyCode::Char('*'))], || {
+                TallyMessage::Double
+            });
+            bindings.add(
+                "start-macro",
+                [ctrl('x'), KeyCode::Char('(').into()],
+                || TallyMessage::StartMacro,
+            );
+            bindings.add("stop-macro", [ctrl('x'), KeyCode::Char(')').into()], || {
+                TallyMessage::StopMacro
+            });
+            bindings.add(
+                "replay-macro",
+                [ctrl('x'), KeyCode::Char('e').into()],
+                || TallyMessage::ReplayMacro(1),
+            );
+            bindings.add(
+                "replay-macro-3",
+                [ctrl('x'), KeyCode::Char('3').into()],
+                || TallyMessage::ReplayMacro(3),
+            );
+        }
+    }
+
+    #[test]
+    fn macros_record_and_replay_keys() {
+        let ctrl_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        let mut app = TestBackend::new(Size::new(10, 1), Tally::with(()));
+
+        // Macros are opt-in
+        app.key(ctrl_x).key(KeyCode::Char('('));
+        assert!(app.app().macros().is_none());
+
+        app.app().set_macros_enabled(true);
+        app.key(ctrl_x).key(KeyCode::Char('('));
+        assert_eq!(app.app().macros().unwrap().recording(), Some("m"));
+        app.keys([KeyCode::Char('+'), KeyCode::Char('*')])
+            .key(ctrl_x)
+            .key(KeyCode::Char(')'));
+        app.assert_lines(&["count=2"]);
+
+        // The keys of the stop binding are not part of the macro
+        let macros = app.app().macros().unwrap();
+        assert_eq!(macros.recording(), None);
+        assert_eq!(macros.last(), Some("m"));
+        assert_eq!(
+            macros.get("m"),
+            Some(&[KeyCode::Char('+').into(), KeyCode::Char('*').into()][..])
+        );
+
+        app.key(ctrl_x).key(KeyCode::Char('e'));
+        app.assert_lines(&["count=6"]);
+        app.key(ctrl_x).key(KeyCode::Char('3'));
+        app.assert_lines(&["count=62"]);
+    }
+}
@@ -0,0 +1,348 @@
+//! An optional application store, holding state shared by many components.
+//!
+//! The store holds a single value implementing [`State`](trait.State.html),
+//! which is changed only by dispatching actions to it with
+//! [`ComponentLink::dispatch`](../struct.ComponentLink.html#method.dispatch).
+//! Components subscribe to a slice of the state with
+//! [`ComponentLink::subscribe`](../struct.ComponentLink.html#method.subscribe)
+//! and receive a message whenever an action changes their slice, so they are
+//! only re-rendered when something they show changed.
+//!
+//! ```
+//! # use zi::{components::text::{Text, TextProperties}, prelude::*, store::{State, Store}};
+//! #[derive(Default)]
+//! struct Counters {
+//!     left: usize,
+//!     right: usize,
+//! }
+//!
+//! enum Action {
+//!     IncrementLeft,
+//!     IncrementRight,
+//! }
+//!
+//! impl State for Counters {
+//!     type Action = Action;
+//!
+//!     fn reduce(&mut self, action: Self::Action) {
+//!         match action {
+//!             Action::IncrementLeft => self.left += 1,
+//!             Action::IncrementRight => self.right += 1,
+//!         }
+//!     }
+//! }
+//!
+//! struct Left {
+//!     count: usize,
+//! }
+//!
+//! impl Component for Left {
+//!     type Message = usize;
+//!     type Properties = ();
+//!
+//!     fn create(_: (), _: Rect, link: ComponentLink<Self>) -> Self {
+//!         // Only re-rendered when the left counter changes
+//!         let count = link.subscribe(|counters: &Counters| counters.left, |count| count);
+//!         Self {
+//!             count: count.unwrap_or_default(),
+//!         }
+//!     }
+//!
+//!     fn update(&mut self, count: usize) -> ShouldRender {
+//!         self.count = count;
+//!         ShouldRender::Yes
+//!     }
+//!
+//!     fn view(&self) -> Layout {
+//!         Text::with(TextProperties::new().content(self.count.to_string()))
+//!     }
+//! }
+//!
+//! let store = Store::new(Counters::default());
+//! ```
+
+use std::{
+    any::{self, Any},
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+use crate::component::template::{ComponentId, DynamicMessage};
+
+/// The state of a store and how actions change it.
+pub trait State: Send + Sync + 'static {
+    /// The actions which can be dispatched to the store.
+    type Action: Send + 'static;
+
+    /// Applies an action to the state.
+    fn reduce(&mut self, action: Self::Action);
+}
+
+/// A store holding the shared state of an application, see the
+/// [module documentation](index.html). Installed with
+/// [`App::set_store`](../app/struct.App.html#method.set_store).
+pub struct Store {
+    state: Box<dyn DynamicState>,
+    subscriptions: Vec<Subscription>,
+}
+
+impl Store {
+    /// Creates a store with the initial state.
+    pub fn new<StateT: State>(state: StateT) -> Self {
+        Self {
+            state: Box::new(state),
+            subscriptions: Vec::new(),
+        }
+    }
+
+    /// Returns a slice of the state, or `None` if the store doesn't hold a
+    /// `StateT`.
+    pub fn select<StateT: State, SliceT>(
+        &self,
+        selector: impl FnOnce(&StateT) -> SliceT,
+    ) -> Option<SliceT> {
+        self.state.as_any().downcast_ref().map(selector)
+    }
+
+    /// Applies an action to the state. Returns the messages for components
+    /// whose selected slice changed.
+    pub(crate) fn dispatch(
+        &mut self,
+        action: Box<dyn Any + Send>,
+    ) -> Vec<(ComponentId, DynamicMessage)> {
+        if !self.state.reduce(action) {
+            log::warn!(
+                "Ignoring an action of the wrong type for the store's state `{}`",
+                self.state.type_name()
+            );
+            return Vec::new();
+        }
+        let state = self.state.as_any();
+        self.subscriptions
+            .iter_mut()
+            .filter_map(|subscription| {
+                (subscription.changed)(state).map(|message| (subscription.component_id, message))
+            })
+            .collect()
+    }
+
+    pub(crate) fn subscribe(&mut self, component_id: ComponentId, changed: SubscriptionFn) {
+        self.subscriptions.push(Subscription {
+            component_id,
+            changed,
+        });
+    }
+
+    /// Drops the subscriptions of unmounted components.
+    pub(crate) fn unsubscribe(&mut self, component_ids: &[ComponentId]) {
+        self.subscriptions
+            .retain(|subscription| !component_ids.contains(&subscription.component_id));
+    }
+}
+
+impl fmt::Debug for Store {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "Store({}, {} subscriptions)",
+            self.state.type_name(),
+            self.subscriptions.len()
+        )
+    }
+}
+
+/// Selects a slice of the state and returns a message for the subscribed
+/// component if it changed since the last call.
+pub(crate) type SubscriptionFn = Box<dyn FnMut(&dyn Any) -> Option<DynamicMessage> + Send + Sync>;
+
+struct Subscription {
+    component_id: ComponentId,
+    changed: SubscriptionFn,
+}
+
+trait DynamicState: Send + Sync {
+    /// Applies an action, returning `false` if it has the wrong type.
+    fn reduce(&mut self, action: Box<dyn Any + Send>) -> bool;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn type_name(&self) -> &'static str;
+}
+
+impl<StateT: State> DynamicState for StateT {
+    fn reduce(&mut self, action: Box<dyn Any + Send>) -> bool {
+        match action.downcast() {
+            Ok(action) => {
+                State::reduce(self, *action);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        any::type_name::<StateT>()
+    }
+}
+
+/// The store of an app, if any, shared with the links of its components.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SharedStore(Arc<RwLock<Option<Store>>>);
+
+impl SharedStore {
+    pub(crate) fn set(&self, store: Store) {
+        *self.0.write().expect("store lock to not be poisoned") = Some(store);
+    }
+
+    /// Runs a function with the store, returning `None` if there isn't one.
+    pub(crate) fn read<ResultT>(
+        &self,
+        function: impl FnOnce(&Store) -> ResultT,
+    ) -> Option<ResultT> {
+        self.0
+            .read()
+            .expect("store lock to not be poisoned")
+            .as_ref()
+            .map(function)
+    }
+
+    /// Runs a function with the store, returning `None` if there isn't one.
+    pub(crate) fn write<ResultT>(
+        &self,
+        function: impl FnOnce(&mut Store) -> ResultT,
+    ) -> Option<ResultT> {
+        self.0
+            .write()
+            .expect("store lock to not be poisoned")
+            .as_mut()
+            .map(function)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::text::{Text, TextProperties},
+        prelude::*,
+        testing::TestBackend,
+    };
+
+    #[derive(Default)]
+    struct Counters {
+        left: usize,
+        right: usize,
+    }
+
+    #[derive(Clone, Copy)]
+    enum Side {
+        Left,
+        Right,
+    }
+
+    impl State for Counters {
+        type Action = Side;
+
+        fn reduce(&mut self, side: Self::Action) {
+            match side {
+                Side::Left => self.left += 1,
+                Side::Right => self.right += 1,
+            }
+        }
+    }
+
+    /// Shows one of the counters and how often it was updated.
+    struct CounterView {
+        count: usize,
+        updates: usize,
+    }
+
+    impl Component for CounterView {
+        type Message = usize;
+        type Properties = Side;
+
+        fn create(side: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            let count = link.subscribe(
+                move |counters: &Counters| match side {
+                    Side::Left => counters.left,
+                    Side::Right => counters.right,
+                },
+                |count| count,
+            );
+            Self {
+                count: count.expect("store to hold counters"),
+                updates: 0,
+            }
+        }
+
+        fn update(&mut self, count: usize) -> ShouldRender {
+            self.count = count;
+            self.updates += 1;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(format!("{}/{}", self.count, self.updates)))
+        }
+    }
+
+    /// Dispatches actions to the store, `l` and `r` increment the counters.
+    struct Dispatcher {
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Dispatcher {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self { link }
+        }
+
+        fn view(&self) -> Layout {
+            Layout::row([
+                Item::fixed(4)(CounterView::with(Side::Left)),
+                Item::fixed(4)(CounterView::with(Side::Right)),
+            ])
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            let link = self.link.clone();
+            bindings.add("left", [KeyEvent::from(KeyCode::Char('l'))], move || {
+                link.dispatch(Side::Left)
+            });
+            let link = self.link.clone();
+            bindings.add("right", [KeyEvent::from(KeyCode::Char('r'))], move || {
+                link.dispatch(Side::Right)
+            });
+        }
+    }
+
+    #[test]
+    fn store_subscribers_are_updated_when_their_slice_changes() {
+        let mut app = TestBackend::with_store(
+            Size::new(8, 1),
+            Dispatcher::with(()),
+            Store::new(Counters::default()),
+        );
+        app.assert_lines(&["0/0 0/0"]);
+
+        // Only the subscriber whose slice changed is updated
+        app.keys([KeyCode::Char('l'), KeyCode::Char('l')]);
+        app.assert_lines(&["2/2 0/0"]);
+        app.key(KeyCode::Char('r'));
+        app.assert_lines(&["2/2 1/1"]);
+        assert_eq!(
+            app.app().select(|counters: &Counters| counters.left),
+            Some(2)
+        );
+    }
+}
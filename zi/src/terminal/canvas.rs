@@ -3,7 +3,7 @@ use std::{self, cmp, iter};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
-use super::{Position, Size};
+use super::{AnsiColour, Position, Size};
 use crate::terminal::Rect;
 
 /// An extended grapheme cluster represented as a `SmallString`.
@@ -138,23 +138,39 @@ impl Canvas {
         current_offset - initial_offset
     }
 
+    /// Copies `source` to this canvas, with its top left corner at the origin
+    /// of `region`. Anything that doesn't fit on this canvas is left out.
     #[inline]
     pub fn copy_region(&mut self, source: &Self, region: Rect) {
-        let y_range = cmp::min(region.origin.y, self.size.height)
-            ..cmp::min(region.origin.y + source.size.height, self.size.height);
-        let x_range = cmp::min(region.origin.x, self.size.width)
-            ..cmp::min(region.origin.x + source.size.width, self.size.width);
+        self.copy_from(
+            source,
+            Rect::new(Position::new(0, 0), source.size),
+            region.origin,
+        );
+    }
 
-        for y in y_range {
-            self.buffer[y * self.size.width + x_range.start..y * self.size.width + x_range.end]
-                .iter_mut()
-                .zip(
-                    source.buffer[(y - region.origin.y) * source.size.width
-                        ..(y - region.origin.y) * source.size.width
-                            + (x_range.end - region.origin.x)]
-                        .iter(),
-                )
-                .for_each(|(textel, other)| *textel = other.clone());
+    /// Copies the part of `source` inside `region` to this canvas, with its
+    /// top left corner at `position`. The region is clipped to both canvases.
+    #[inline]
+    pub fn copy_from(&mut self, source: &Self, region: Rect, position: Position) {
+        let width = cmp::min(
+            cmp::min(region.max_x(), source.size.width).saturating_sub(region.origin.x),
+            self.size.width.saturating_sub(position.x),
+        );
+        let height = cmp::min(
+            cmp::min(region.max_y(), source.size.height).saturating_sub(region.origin.y),
+            self.size.height.saturating_sub(position.y),
+        );
+
+        if width == 0 {
+            return;
+        }
+
+        for y in 0..height {
+            let start = (position.y + y) * self.size.width + position.x;
+            let source_start = (region.origin.y + y) * source.size.width + region.origin.x;
+            self.buffer[start..start + width]
+                .clone_from_slice(&source.buffer[source_start..source_start + width]);
         }
     }
 
@@ -197,12 +213,35 @@ impl std::fmt::Display for Canvas {
 
 /// Specifies how content should be styled. This represents a subset of the ANSI
 /// available styles which is widely supported by terminal emulators.
+///
+/// Terminals that don't support an attribute typically ignore it. Use struct
+/// update syntax to set attributes which don't have a constructor:
+///
+/// ```
+/// # use zi::Style;
+/// let comment = Style {
+///     italic: true,
+///     dim: true,
+///     ..Style::terminal_default()
+/// };
+/// ```
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Style {
     pub background: Background,
     pub foreground: Foreground,
     pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
     pub underline: bool,
+    /// The shape of the underline, when `underline` is set.
+    pub underline_style: UnderlineStyle,
+    /// The colour of the underline. `Colour::Default` draws it in the
+    /// foreground colour.
+    pub underline_colour: Colour,
+    pub blink: bool,
+    /// Swaps the foreground and background colours when drawn.
+    pub reverse: bool,
+    pub strikethrough: bool,
 }
 
 impl Style {
@@ -212,44 +251,49 @@ impl Style {
         Style::normal(Colour::black(), Colour::white())
     }
 
+    /// A style using the terminal's default colours, so that content matches
+    /// the user's terminal theme.
+    #[inline]
+    pub const fn terminal_default() -> Self {
+        Style::normal(Colour::Default, Colour::Default)
+    }
+
     #[inline]
     pub const fn normal(background: Background, foreground: Foreground) -> Self {
         Self {
             background,
             foreground,
             bold: false,
+            dim: false,
+            italic: false,
             underline: false,
+            underline_style: UnderlineStyle::Single,
+            underline_colour: Colour::Default,
+            blink: false,
+            reverse: false,
+            strikethrough: false,
         }
     }
 
     #[inline]
     pub const fn bold(background: Background, foreground: Foreground) -> Self {
         Self {
-            background,
-            foreground,
             bold: true,
-            underline: false,
+            ..Style::normal(background, foreground)
         }
     }
 
     #[inline]
     pub const fn underline(background: Background, foreground: Foreground) -> Self {
         Self {
-            background,
-            foreground,
-            bold: false,
             underline: true,
+            ..Style::normal(background, foreground)
         }
     }
 
     #[inline]
     pub const fn same_colour(colour: Colour) -> Self {
-        Self {
-            background: colour,
-            foreground: colour,
-            bold: false,
-            underline: false,
-        }
+        Style::normal(colour, colour)
     }
 
     #[inline]
@@ -257,8 +301,7 @@ impl Style {
         Self {
             background: self.foreground,
             foreground: self.background,
-            bold: self.bold,
-            underline: self.underline,
+            ..self
         }
     }
 }
@@ -270,39 +313,53 @@ impl Default for Style {
     }
 }
 
-/// An RGB encoded colour, 1-byte per channel.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Colour {
-    pub red: u8,
-    pub green: u8,
-    pub blue: u8,
+/// The shape of an underline. Terminals without support for the other shapes
+/// typically draw a single underline instead.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum UnderlineStyle {
+    Single,
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
+/// A colour, either RGB encoded, an entry of the terminal's palette or the
+/// terminal's default.
+///
+/// Backends replace colours the terminal can't display with the nearest one
+/// it can, see [`Colour::downsample`](enum.Colour.html#method.downsample).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Colour {
+    /// The terminal's default foreground or background colour, as configured
+    /// by the user.
+    Default,
+    /// One of the 16 ANSI colours, as configured by the user.
+    Ansi(AnsiColour),
+    /// An entry of the xterm 256-colour palette. Entries 0 to 15 are the ANSI
+    /// colours, followed by a 6x6x6 colour cube and a 24 step grey ramp.
+    Indexed(u8),
+    /// An RGB encoded colour, 1-byte per channel.
+    Rgb { red: u8, green: u8, blue: u8 },
 }
 
 impl Colour {
     /// Creates a colour from the provided RGB values.
     #[inline]
     pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
-        Self { red, green, blue }
+        Self::Rgb { red, green, blue }
     }
 
     /// Returns black.
     #[inline]
     pub const fn black() -> Self {
-        Self {
-            red: 0,
-            green: 0,
-            blue: 0,
-        }
+        Self::rgb(0, 0, 0)
     }
 
     /// Returns white.
     #[inline]
     pub const fn white() -> Self {
-        Self {
-            red: 255,
-            green: 255,
-            blue: 255,
-        }
+        Self::rgb(255, 255, 255)
     }
 }
 
@@ -386,7 +443,8 @@ const UPPER_HALF_BLOCK: &str = "▀";
 
 #[cfg(test)]
 mod tests {
-    use super::{GraphemeCluster, Style, Textel};
+    use super::{Canvas, GraphemeCluster, Style, Textel};
+    use crate::terminal::{Position, Rect, Size};
 
     #[test]
     fn size_of_style() {
@@ -407,4 +465,39 @@ mod tests {
             std::mem::size_of::<Option<GraphemeCluster>>()
         );
     }
+
+    #[test]
+    fn copies_are_clipped_to_both_canvases() {
+        let mut source = Canvas::new(Size::new(3, 2));
+        source.draw_str(0, 0, Style::default(), "abc");
+        source.draw_str(0, 1, Style::default(), "def");
+        let grapheme = |canvas: &Canvas, x, y| {
+            canvas
+                .textel(x, y)
+                .as_ref()
+                .map(|textel| textel.grapheme.to_string())
+        };
+
+        let mut canvas = Canvas::new(Size::new(4, 2));
+        canvas.copy_region(&source, Rect::new(Position::new(2, 1), Size::new(3, 2)));
+        assert_eq!(grapheme(&canvas, 2, 1).as_deref(), Some("a"));
+        assert_eq!(grapheme(&canvas, 3, 1).as_deref(), Some("b"));
+
+        // Regions outside of either canvas copy nothing
+        canvas.copy_region(&source, Rect::new(Position::new(9, 0), Size::new(3, 2)));
+        canvas.copy_from(
+            &source,
+            Rect::new(Position::new(5, 5), Size::new(1, 1)),
+            Position::new(0, 0),
+        );
+
+        canvas.copy_from(
+            &source,
+            Rect::new(Position::new(1, 1), Size::new(5, 5)),
+            Position::new(0, 0),
+        );
+        assert_eq!(grapheme(&canvas, 0, 0).as_deref(), Some("e"));
+        assert_eq!(grapheme(&canvas, 1, 0).as_deref(), Some("f"));
+        assert_eq!(grapheme(&canvas, 0, 1).as_deref(), Some(""));
+    }
 }
@@ -1,10 +1,20 @@
 use bitflags::bitflags;
-use std::hash::{Hash, Hasher};
+use std::{
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use super::Position;
 
 /// Input event
 #[derive(Debug)]
 pub enum Event {
     Key(KeyEvent),
+    Mouse(MouseEvent),
+    /// Text pasted into the terminal as a single chunk (bracketed paste).
+    Paste(String),
 }
 
 #[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
@@ -125,3 +135,209 @@ impl Hash for KeyEvent {
         modifiers.hash(state);
     }
 }
+
+/// Formats a key using Emacs style notation, e.g. `C-x`, `A-RET` or `S-Left`.
+///
+/// Modifiers are written as `C-` (control), `A-` (alt) and `S-` (shift)
+/// prefixes. Shift is implied by upper case ASCII letters and written out
+/// for any other key, e.g. `S-1`. The output can be parsed back with
+/// `FromStr`.
+impl fmt::Display for KeyEvent {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let KeyEvent { code, modifiers } = self.normalize_case();
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            formatter.write_str("C-")?;
+        }
+        if modifiers.contains(KeyModifiers::ALT) {
+            formatter.write_str("A-")?;
+        }
+        if modifiers.contains(KeyModifiers::SHIFT)
+            && !matches!(code, KeyCode::Char(character) if character.is_ascii_uppercase())
+        {
+            formatter.write_str("S-")?;
+        }
+        match code {
+            KeyCode::Char(' ') => formatter.write_str("SPC"),
+            KeyCode::Char(character) => write!(formatter, "{}", character),
+            KeyCode::Enter => formatter.write_str("RET"),
+            KeyCode::Tab => formatter.write_str("TAB"),
+            KeyCode::Esc => formatter.write_str("ESC"),
+            KeyCode::F(number) => write!(formatter, "F{}", number),
+            code => write!(formatter, "{:?}", code),
+        }
+    }
+}
+
+/// Parses a key written in either Emacs style notation, e.g. `C-x`, `A-RET`,
+/// or angle bracket notation, e.g. `<ctrl-x>`, `<alt-enter>`.
+///
+/// ```
+/// # use zi::{KeyCode, KeyEvent, KeyModifiers};
+/// let key = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+/// assert_eq!("C-x".parse(), Ok(key));
+/// assert_eq!("<ctrl-x>".parse(), Ok(key));
+/// assert_eq!(key.to_string(), "C-x");
+/// ```
+impl FromStr for KeyEvent {
+    type Err = ParseKeyError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || ParseKeyError::new(input);
+        // Modifier names are case sensitive in Emacs notation, but not
+        // within angle brackets
+        let (mut remaining, prefixes, ignore_case): (_, &[(&str, KeyModifiers)], _) = match input
+            .strip_prefix('<')
+            .and_then(|key| key.strip_suffix('>'))
+        {
+            Some(key) if !key.is_empty() => (key, ANGLE_MODIFIERS, true),
+            _ => (input, EMACS_MODIFIERS, false),
+        };
+
+        let mut modifiers = KeyModifiers::empty();
+        'modifiers: loop {
+            for (prefix, modifier) in prefixes {
+                let stripped = remaining
+                    .get(..prefix.len())
+                    .filter(|candidate| {
+                        *candidate == *prefix
+                            || (ignore_case && candidate.eq_ignore_ascii_case(prefix))
+                    })
+                    .and_then(|_| remaining[prefix.len()..].strip_prefix('-'))
+                    .filter(|key| !key.is_empty());
+                if let Some(key) = stripped {
+                    modifiers.insert(*modifier);
+                    remaining = key;
+                    continue 'modifiers;
+                }
+            }
+            break;
+        }
+
+        let code = parse_key_code(remaining).ok_or_else(error)?;
+        Ok(KeyEvent::new(code, modifiers))
+    }
+}
+
+const EMACS_MODIFIERS: &[(&str, KeyModifiers)] = &[
+    ("C", KeyModifiers::CONTROL),
+    ("A", KeyModifiers::ALT),
+    ("M", KeyModifiers::ALT),
+    ("S", KeyModifiers::SHIFT),
+];
+
+const ANGLE_MODIFIERS: &[(&str, KeyModifiers)] = &[
+    ("ctrl", KeyModifiers::CONTROL),
+    ("control", KeyModifiers::CONTROL),
+    ("alt", KeyModifiers::ALT),
+    ("meta", KeyModifiers::ALT),
+    ("shift", KeyModifiers::SHIFT),
+];
+
+/// Names of keys other than characters and function keys. Matched ignoring
+/// case when parsing.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("SPC", KeyCode::Char(' ')),
+    ("Space", KeyCode::Char(' ')),
+    ("RET", KeyCode::Enter),
+    ("Enter", KeyCode::Enter),
+    ("Return", KeyCode::Enter),
+    ("TAB", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("ESC", KeyCode::Esc),
+    ("Escape", KeyCode::Esc),
+    ("Backspace", KeyCode::Backspace),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Delete", KeyCode::Delete),
+    ("DEL", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Null", KeyCode::Null),
+];
+
+fn parse_key_code(name: &str) -> Option<KeyCode> {
+    let mut characters = name.chars();
+    if let (Some(character), None) = (characters.next(), characters.next()) {
+        return Some(KeyCode::Char(character));
+    }
+
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, code)| *code)
+        .or_else(|| {
+            name.strip_prefix(|prefix| prefix == 'F' || prefix == 'f')
+                .and_then(|number| number.parse().ok())
+                .map(KeyCode::F)
+        })
+}
+
+/// Error returned when parsing a key or key sequence fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError(String);
+
+impl ParseKeyError {
+    pub(crate) fn new(input: &str) -> Self {
+        Self(input.into())
+    }
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "invalid key `{}`", self.0)
+    }
+}
+
+impl Error for ParseKeyError {}
+
+/// Represents a mouse event.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct MouseEvent {
+    /// The kind of mouse event.
+    pub kind: MouseEventKind,
+    /// The position of the mouse cursor. The runtime delivers the event to
+    /// components with a position relative to the origin of their frame.
+    pub position: Position,
+    /// Key modifiers active when the event occurred.
+    pub modifiers: KeyModifiers,
+}
+
+impl MouseEvent {
+    pub const fn new(kind: MouseEventKind, position: Position, modifiers: KeyModifiers) -> Self {
+        Self {
+            kind,
+            position,
+            modifiers,
+        }
+    }
+}
+
+/// The kind of a mouse event.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum MouseEventKind {
+    /// A mouse button was pressed.
+    Down(MouseButton),
+    /// A mouse button was released.
+    Up(MouseButton),
+    /// The mouse cursor moved while a button was held down.
+    Drag(MouseButton),
+    /// The mouse cursor moved while no button was held down.
+    Moved,
+    /// The mouse wheel was scrolled down (towards the user).
+    ScrollDown,
+    /// The mouse wheel was scrolled up (away from the user).
+    ScrollUp,
+}
+
+/// Represents a mouse button.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
@@ -6,8 +6,13 @@
 
 pub use canvas::{
     Background, Canvas, Colour, Foreground, GraphemeCluster, SquarePixelGrid, Style, Textel,
+    UnderlineStyle,
 };
-pub use input::{Event, KeyCode, KeyEvent, KeyModifiers};
+pub use input::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind, ParseKeyError,
+};
+pub use palette::{AnsiColour, ColourSupport};
+pub use snapshot::SnapshotError;
 
 /// A 2D rectangle with usize coordinates. Re-exported from
 /// [euclid](https://docs.rs/euclid).
@@ -23,3 +28,5 @@ pub type Size = euclid::default::Size2D<usize>;
 
 pub(crate) mod canvas;
 pub(crate) mod input;
+pub(crate) mod palette;
+pub mod snapshot;
@@ -0,0 +1,311 @@
+//! The 16 ANSI colours, the xterm 256-colour palette and the conversion of
+//! colours for terminals which can't display all of them.
+
+use super::{Colour, Style};
+
+/// One of the 16 ANSI colours. Their exact values are chosen by the terminal's
+/// theme, so they blend in with the user's configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AnsiColour {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl AnsiColour {
+    /// All ANSI colours, in palette order.
+    pub const ALL: [AnsiColour; 16] = [
+        AnsiColour::Black,
+        AnsiColour::Red,
+        AnsiColour::Green,
+        AnsiColour::Yellow,
+        AnsiColour::Blue,
+        AnsiColour::Magenta,
+        AnsiColour::Cyan,
+        AnsiColour::White,
+        AnsiColour::BrightBlack,
+        AnsiColour::BrightRed,
+        AnsiColour::BrightGreen,
+        AnsiColour::BrightYellow,
+        AnsiColour::BrightBlue,
+        AnsiColour::BrightMagenta,
+        AnsiColour::BrightCyan,
+        AnsiColour::BrightWhite,
+    ];
+
+    /// Returns the colour's index in the palette, from 0 to 15.
+    #[inline]
+    pub const fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the colour with the given index, or `None` if it isn't one of
+    /// the first 16 entries of the palette.
+    #[inline]
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(index)).copied()
+    }
+
+    /// Returns the name of the colour in kebab case, e.g. `bright-red`.
+    pub fn name(self) -> &'static str {
+        match self {
+            AnsiColour::Black => "black",
+            AnsiColour::Red => "red",
+            AnsiColour::Green => "green",
+            AnsiColour::Yellow => "yellow",
+            AnsiColour::Blue => "blue",
+            AnsiColour::Magenta => "magenta",
+            AnsiColour::Cyan => "cyan",
+            AnsiColour::White => "white",
+            AnsiColour::BrightBlack => "bright-black",
+            AnsiColour::BrightRed => "bright-red",
+            AnsiColour::BrightGreen => "bright-green",
+            AnsiColour::BrightYellow => "bright-yellow",
+            AnsiColour::BrightBlue => "bright-blue",
+            AnsiColour::BrightMagenta => "bright-magenta",
+            AnsiColour::BrightCyan => "bright-cyan",
+            AnsiColour::BrightWhite => "bright-white",
+        }
+    }
+
+    /// Returns the colour with the given name, as returned by `name`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|colour| colour.name() == name)
+    }
+
+    /// The colour in xterm's default theme, used as a stand-in for the
+    /// terminal's actual theme when picking the nearest ANSI colour.
+    #[inline]
+    fn xterm_rgb(self) -> Rgb {
+        XTERM_ANSI_RGB[usize::from(self.index())]
+    }
+}
+
+/// How many colours a terminal can display.
+///
+/// Colours which the terminal can't display are replaced by the nearest one
+/// it can, see [`Colour::downsample`](enum.Colour.html#method.downsample).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ColourSupport {
+    /// No colours, only the terminal's default foreground and background.
+    Monochrome,
+    /// The 16 ANSI colours.
+    Ansi16,
+    /// The xterm 256-colour palette.
+    Ansi256,
+    /// 24-bit RGB colours.
+    TrueColour,
+}
+
+impl Colour {
+    /// Creates one of the 16 ANSI colours.
+    #[inline]
+    pub const fn ansi(colour: AnsiColour) -> Self {
+        Self::Ansi(colour)
+    }
+
+    /// Creates an entry of the xterm 256-colour palette.
+    #[inline]
+    pub const fn indexed(index: u8) -> Self {
+        Self::Indexed(index)
+    }
+
+    /// Returns the nearest colour which can be displayed with the given colour
+    /// support.
+    ///
+    /// RGB colours become the nearest entry of the 6x6x6 colour cube or the
+    /// grey ramp of the 256-colour palette; the first 16 entries are skipped,
+    /// as their values depend on the terminal's theme. With 16 colours, the
+    /// nearest ANSI colour in xterm's default theme is used. Without colour
+    /// support, every colour becomes `Colour::Default`.
+    pub fn downsample(self, support: ColourSupport) -> Self {
+        match (support, self) {
+            (ColourSupport::TrueColour, _) | (_, Colour::Default) => self,
+            (ColourSupport::Monochrome, _) => Colour::Default,
+            (ColourSupport::Ansi256, Colour::Rgb { red, green, blue }) => {
+                Colour::Indexed(nearest_indexed([red, green, blue]))
+            }
+            (ColourSupport::Ansi256, _) => self,
+            (ColourSupport::Ansi16, Colour::Ansi(_)) => self,
+            (ColourSupport::Ansi16, Colour::Indexed(index)) => {
+                match AnsiColour::from_index(index) {
+                    Some(colour) => Colour::Ansi(colour),
+                    None => Colour::Ansi(nearest_ansi(indexed_rgb(index))),
+                }
+            }
+            (ColourSupport::Ansi16, Colour::Rgb { red, green, blue }) => {
+                Colour::Ansi(nearest_ansi([red, green, blue]))
+            }
+        }
+    }
+}
+
+impl Style {
+    /// Returns the style with all its colours downsampled, see
+    /// [`Colour::downsample`](enum.Colour.html#method.downsample).
+    pub fn downsample(self, support: ColourSupport) -> Self {
+        Self {
+            background: self.background.downsample(support),
+            foreground: self.foreground.downsample(support),
+            underline_colour: self.underline_colour.downsample(support),
+            ..self
+        }
+    }
+}
+
+type Rgb = [u8; 3];
+
+const XTERM_ANSI_RGB: [Rgb; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// The channel values of the 6x6x6 colour cube, palette entries 16 to 231.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The first entry of the grey ramp, which has 24 shades from 8 to 238.
+const GREY_RAMP_START: u8 = 232;
+
+/// Returns the RGB value of a 256-colour palette entry.
+fn indexed_rgb(index: u8) -> Rgb {
+    match index {
+        0..=15 => XTERM_ANSI_RGB[usize::from(index)],
+        16..=231 => {
+            let cube = index - 16;
+            [
+                CUBE_LEVELS[usize::from(cube / 36)],
+                CUBE_LEVELS[usize::from(cube / 6 % 6)],
+                CUBE_LEVELS[usize::from(cube % 6)],
+            ]
+        }
+        _ => {
+            let level = 8 + 10 * (index - GREY_RAMP_START);
+            [level, level, level]
+        }
+    }
+}
+
+fn nearest_indexed(rgb: Rgb) -> u8 {
+    let nearest_level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&level| distance(CUBE_LEVELS[level], channel))
+            .unwrap_or(0) as u8
+    };
+    let cube_index =
+        16 + 36 * nearest_level(rgb[0]) + 6 * nearest_level(rgb[1]) + nearest_level(rgb[2]);
+
+    let mean = rgb.iter().map(|&channel| u32::from(channel)).sum::<u32>() / 3;
+    let grey_index = GREY_RAMP_START + (mean.saturating_sub(3) / 10).min(23) as u8;
+
+    if rgb_distance(indexed_rgb(grey_index), rgb) < rgb_distance(indexed_rgb(cube_index), rgb) {
+        grey_index
+    } else {
+        cube_index
+    }
+}
+
+fn nearest_ansi(rgb: Rgb) -> AnsiColour {
+    AnsiColour::ALL
+        .iter()
+        .copied()
+        .min_by_key(|colour| rgb_distance(colour.xterm_rgb(), rgb))
+        .unwrap_or(AnsiColour::Black)
+}
+
+#[inline]
+fn distance(a: u8, b: u8) -> u32 {
+    let difference = u32::from(if a > b { a - b } else { b - a });
+    difference * difference
+}
+
+/// The squared euclidean distance between two colours.
+#[inline]
+fn rgb_distance(a: Rgb, b: Rgb) -> u32 {
+    (0..3).map(|channel| distance(a[channel], b[channel])).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_colours_are_downsampled_to_the_nearest_palette_entry() {
+        let orange = Colour::rgb(250, 130, 10);
+        assert_eq!(orange.downsample(ColourSupport::TrueColour), orange);
+        assert_eq!(
+            orange.downsample(ColourSupport::Ansi256),
+            Colour::indexed(208)
+        );
+        assert_eq!(
+            orange.downsample(ColourSupport::Ansi16),
+            Colour::ansi(AnsiColour::Yellow)
+        );
+        assert_eq!(
+            orange.downsample(ColourSupport::Monochrome),
+            Colour::Default
+        );
+
+        // Greys use the grey ramp rather than the colour cube
+        let grey = Colour::rgb(120, 120, 120);
+        assert_eq!(
+            grey.downsample(ColourSupport::Ansi256),
+            Colour::indexed(243)
+        );
+        assert_eq!(
+            Colour::black().downsample(ColourSupport::Ansi256),
+            Colour::indexed(16)
+        );
+        assert_eq!(
+            Colour::white().downsample(ColourSupport::Ansi256),
+            Colour::indexed(231)
+        );
+
+        // Palette colours are kept if they can be displayed
+        assert_eq!(
+            Colour::indexed(9).downsample(ColourSupport::Ansi16),
+            Colour::ansi(AnsiColour::BrightRed)
+        );
+        assert_eq!(
+            Colour::indexed(196).downsample(ColourSupport::Ansi16),
+            Colour::ansi(AnsiColour::BrightRed)
+        );
+        assert_eq!(
+            Colour::ansi(AnsiColour::Cyan).downsample(ColourSupport::Ansi256),
+            Colour::ansi(AnsiColour::Cyan)
+        );
+        assert_eq!(
+            Colour::Default.downsample(ColourSupport::Ansi16),
+            Colour::Default
+        );
+    }
+}
@@ -0,0 +1,506 @@
+//! A human readable text format for canvases, meant for snapshot tests.
+//!
+//! A snapshot consists of a header with the size of the canvas, the text
+//! content of each row, a grid assigning a style key to each textel and a
+//! legend describing the style behind each key:
+//!
+//! ```text
+//! canvas 6x2
+//! |Hello |
+//! |中 !  |
+//! styles
+//! |aaaaab|
+//! |a.aaab|
+//! a: bg=#000000 fg=#ffffff
+//! b: bg=#000000 fg=#ff0000 bold underline
+//! ```
+//!
+//! A `.` in the style grid marks a position without a textel, i.e. one covered
+//! by a wide grapheme to its left.
+//!
+//! Colours are written as `#rrggbb`, by name for the ANSI colours (`red`,
+//! `bright-red`, ...), as `colour:N` for entries of the 256-colour palette or
+//! as `default` for the terminal's default colour. Besides `bold` and
+//! `underline`, the attributes `dim`, `italic`, `blink`, `reverse` and
+//! `strikethrough` are listed when set. Underlines other than a single one are
+//! written as `underline=double` (or `curly`, `dotted`, `dashed`) and a
+//! coloured underline as `underline-colour=#rrggbb`. Style keys are assigned
+//! in order of first appearance, so a snapshot of a given canvas is always the
+//! same. Once the ASCII letters, digits and symbols are used up, keys continue
+//! with the characters from U+0100 onwards.
+
+use std::{collections::HashMap, error::Error, fmt, fmt::Write};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{AnsiColour, Canvas, Colour, Size, Style, Textel, UnderlineStyle};
+
+/// Keys used for styles in the style grid, in order of assignment.
+const STYLE_KEYS: &str =
+    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!#$%&*+-/:;<=>?@^_~";
+
+/// The first character used as a style key after `STYLE_KEYS`.
+const FIRST_FALLBACK_KEY: u32 = 0x100;
+
+/// Returns all style keys in order of assignment.
+fn style_keys() -> impl Iterator<Item = char> {
+    STYLE_KEYS
+        .chars()
+        .chain((FIRST_FALLBACK_KEY..=u32::from(char::MAX)).filter_map(char::from_u32))
+}
+
+/// Marks a position without a textel in the style grid.
+const EMPTY_KEY: char = '.';
+
+/// Error returned when parsing a malformed snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotError {
+    /// The 1-based line number where the error occurred.
+    pub line: usize,
+    pub message: String,
+}
+
+impl SnapshotError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl Canvas {
+    /// Serializes the canvas to the snapshot text format. See the
+    /// [`snapshot`](snapshot/index.html) module for a description.
+    ///
+    /// Empty graphemes are written out as spaces, which is how they are
+    /// presented by backends. This is the only information lost in a
+    /// snapshot: parsing it back gives textels with a space instead.
+    ///
+    /// ```
+    /// # use zi::{Canvas, Size, Style};
+    /// let mut canvas = Canvas::new(Size::new(3, 1));
+    /// canvas.clear(Style::default());
+    /// canvas.draw_str(0, 0, Style::default(), "hi");
+    /// assert_eq!(
+    ///     canvas.to_snapshot(),
+    ///     "canvas 3x1\n|hi |\nstyles\n|aaa|\na: bg=#000000 fg=#ffffff\n"
+    /// );
+    /// ```
+    pub fn to_snapshot(&self) -> String {
+        let Size { width, height, .. } = self.size();
+        let mut snapshot = format!("canvas {}x{}\n", width, height);
+        for row in self.buffer().chunks(width.max(1)).take(height) {
+            snapshot.push('|');
+            for textel in row.iter().flatten() {
+                snapshot.push_str(snapshot_grapheme(textel));
+            }
+            snapshot.push_str("|\n");
+        }
+
+        let mut styles = Vec::new();
+        snapshot.push_str("styles\n");
+        for row in self.buffer().chunks(width.max(1)).take(height) {
+            snapshot.push('|');
+            for textel in row.iter() {
+                snapshot.push(match textel {
+                    Some(textel) => {
+                        let index = styles
+                            .iter()
+                            .position(|style| *style == textel.style)
+                            .unwrap_or_else(|| {
+                                styles.push(textel.style);
+                                styles.len() - 1
+                            });
+                        style_keys()
+                            .nth(index)
+                            .expect("a canvas has fewer distinct styles than unicode characters")
+                    }
+                    None => EMPTY_KEY,
+                });
+            }
+            snapshot.push_str("|\n");
+        }
+
+        for (key, style) in style_keys().zip(styles.iter()) {
+            let _ = writeln!(snapshot, "{}: {}", key, StyleDescription(style));
+        }
+        snapshot
+    }
+
+    /// Parses a canvas from the snapshot text format.
+    ///
+    /// ```
+    /// # use zi::{Canvas, Size};
+    /// let canvas = Canvas::from_snapshot("canvas 2x1\n|ok|\nstyles\n|aa|\na: bg=#000000 fg=#ffffff\n")
+    ///     .unwrap();
+    /// assert_eq!(canvas.size(), Size::new(2, 1));
+    /// assert_eq!(canvas.textel(1, 0).as_ref().unwrap().grapheme, "k");
+    /// ```
+    pub fn from_snapshot(snapshot: &str) -> Result<Canvas, SnapshotError> {
+        let mut lines = snapshot
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+        let mut next_line = |expected: &str| {
+            lines.next().ok_or_else(|| {
+                SnapshotError::new(
+                    snapshot.lines().count() + 1,
+                    format!("unexpected end of snapshot, expected {}", expected),
+                )
+            })
+        };
+
+        // Header
+        let (line_number, line) = next_line("the canvas header")?;
+        let size = line
+            .strip_prefix("canvas ")
+            .and_then(|size| size.split_once('x'))
+            .and_then(|(width, height)| Some(Size::new(width.parse().ok()?, height.parse().ok()?)))
+            .ok_or_else(|| {
+                SnapshotError::new(line_number, "expected a header like `canvas 80x24`")
+            })?;
+
+        // Text content
+        let mut text_rows = Vec::with_capacity(size.height);
+        for _ in 0..size.height {
+            let (line_number, line) = next_line("a row of text")?;
+            text_rows.push((line_number, strip_row_delimiters(line_number, line)?));
+        }
+        let (line_number, line) = next_line("the `styles` separator")?;
+        if line != "styles" {
+            return Err(SnapshotError::new(line_number, "expected `styles`"));
+        }
+
+        // Style grid
+        let mut style_rows = Vec::with_capacity(size.height);
+        for _ in 0..size.height {
+            let (line_number, line) = next_line("a row of style keys")?;
+            let row = strip_row_delimiters(line_number, line)?;
+            if row.chars().count() != size.width {
+                return Err(SnapshotError::new(
+                    line_number,
+                    format!("expected {} style keys", size.width),
+                ));
+            }
+            style_rows.push((line_number, row));
+        }
+
+        // Legend
+        let mut legend = HashMap::new();
+        for (line_number, line) in lines {
+            if line.is_empty() {
+                continue;
+            }
+            let (key, description) = line
+                .split_once(": ")
+                .and_then(|(key, description)| {
+                    let mut chars = key.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(key), None) => Some((key, description)),
+                        _ => None,
+                    }
+                })
+                .ok_or_else(|| SnapshotError::new(line_number, "expected a style legend entry"))?;
+            let style = parse_style(description)
+                .map_err(|message| SnapshotError::new(line_number, message))?;
+            legend.insert(key, style);
+        }
+
+        let mut canvas = Canvas::new(size);
+        for (y, ((text_line, text), (style_line, styles))) in
+            text_rows.into_iter().zip(style_rows).enumerate()
+        {
+            let mut graphemes = text.graphemes(true);
+            for (x, key) in styles.chars().enumerate() {
+                *canvas.textel_mut(x, y) = if key == EMPTY_KEY {
+                    None
+                } else {
+                    let style = *legend.get(&key).ok_or_else(|| {
+                        SnapshotError::new(style_line, format!("unknown style key `{}`", key))
+                    })?;
+                    let grapheme = graphemes.next().ok_or_else(|| {
+                        SnapshotError::new(text_line, format!("missing grapheme at column {}", x))
+                    })?;
+                    Some(Textel {
+                        grapheme: grapheme.into(),
+                        style,
+                    })
+                };
+            }
+            if graphemes.next().is_some() {
+                return Err(SnapshotError::new(
+                    text_line,
+                    "more graphemes than textels in the style grid",
+                ));
+            }
+        }
+
+        Ok(canvas)
+    }
+}
+
+/// Returns the grapheme of a textel as written in a snapshot.
+#[inline]
+fn snapshot_grapheme(textel: &Textel) -> &str {
+    if textel.grapheme.is_empty() {
+        " "
+    } else {
+        textel.grapheme.as_str()
+    }
+}
+
+/// Displays a style using the snapshot legend syntax,
+/// e.g. `bg=#000000 fg=#ffffff bold`.
+pub struct StyleDescription<'a>(pub &'a Style);
+
+impl fmt::Display for StyleDescription<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let Style {
+            background,
+            foreground,
+            bold,
+            dim,
+            italic,
+            underline,
+            underline_style,
+            underline_colour,
+            blink,
+            reverse,
+            strikethrough,
+        } = *self.0;
+        write!(
+            formatter,
+            "bg={} fg={}",
+            ColourDescription(background),
+            ColourDescription(foreground)
+        )?;
+        let attributes = [
+            (bold, "bold"),
+            (dim, "dim"),
+            (italic, "italic"),
+            (blink, "blink"),
+            (reverse, "reverse"),
+            (strikethrough, "strikethrough"),
+        ];
+        for &(enabled, name) in &attributes[..] {
+            if enabled {
+                write!(formatter, " {}", name)?;
+            }
+        }
+        if underline {
+            match UNDERLINE_STYLES
+                .iter()
+                .find(|&&(style, _)| style == underline_style)
+            {
+                Some((_, name)) => write!(formatter, " underline={}", name)?,
+                None => write!(formatter, " underline")?,
+            }
+        }
+        if underline_colour != Colour::Default {
+            write!(
+                formatter,
+                " underline-colour={}",
+                ColourDescription(underline_colour)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Names of the underline styles other than `Single`.
+const UNDERLINE_STYLES: [(UnderlineStyle, &str); 4] = [
+    (UnderlineStyle::Double, "double"),
+    (UnderlineStyle::Curly, "curly"),
+    (UnderlineStyle::Dotted, "dotted"),
+    (UnderlineStyle::Dashed, "dashed"),
+];
+
+struct ColourDescription(Colour);
+
+impl fmt::Display for ColourDescription {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Colour::Default => write!(formatter, "default"),
+            Colour::Ansi(colour) => write!(formatter, "{}", colour.name()),
+            Colour::Indexed(index) => write!(formatter, "colour:{}", index),
+            Colour::Rgb { red, green, blue } => {
+                write!(formatter, "#{:02x}{:02x}{:02x}", red, green, blue)
+            }
+        }
+    }
+}
+
+fn strip_row_delimiters(line_number: usize, line: &str) -> Result<&str, SnapshotError> {
+    line.strip_prefix('|')
+        .and_then(|line| line.strip_suffix('|'))
+        .ok_or_else(|| SnapshotError::new(line_number, "expected a row delimited by `|`"))
+}
+
+fn parse_style(description: &str) -> Result<Style, String> {
+    let mut background = None;
+    let mut foreground = None;
+    let mut style = Style::default();
+    for token in description.split_whitespace() {
+        match token.split_once('=') {
+            Some(("bg", colour)) => background = Some(parse_colour(colour)?),
+            Some(("fg", colour)) => foreground = Some(parse_colour(colour)?),
+            Some(("underline", name)) => {
+                style.underline = true;
+                style.underline_style = UNDERLINE_STYLES
+                    .iter()
+                    .find(|&&(_, style_name)| style_name == name)
+                    .map(|&(underline_style, _)| underline_style)
+                    .ok_or_else(|| format!("unknown underline style `{}`", name))?;
+            }
+            Some(("underline-colour", colour)) => style.underline_colour = parse_colour(colour)?,
+            None if token == "bold" => style.bold = true,
+            None if token == "dim" => style.dim = true,
+            None if token == "italic" => style.italic = true,
+            None if token == "underline" => style.underline = true,
+            None if token == "blink" => style.blink = true,
+            None if token == "reverse" => style.reverse = true,
+            None if token == "strikethrough" => style.strikethrough = true,
+            _ => return Err(format!("unknown style attribute `{}`", token)),
+        }
+    }
+    style.background = background.ok_or("missing background colour `bg=`")?;
+    style.foreground = foreground.ok_or("missing foreground colour `fg=`")?;
+    Ok(style)
+}
+
+fn parse_colour(colour: &str) -> Result<Colour, String> {
+    if colour == "default" {
+        return Ok(Colour::Default);
+    }
+    if let Some(ansi_colour) = AnsiColour::from_name(colour) {
+        return Ok(Colour::Ansi(ansi_colour));
+    }
+    let invalid = || {
+        format!(
+            "invalid colour `{}`, expected `#rrggbb`, an ANSI colour name, \
+             `colour:N` or `default`",
+            colour
+        )
+    };
+    if let Some(index) = colour.strip_prefix("colour:") {
+        return index.parse().map(Colour::Indexed).map_err(|_| invalid());
+    }
+    let hex = colour.strip_prefix('#').ok_or_else(invalid)?;
+    if hex.len() != 6 {
+        return Err(invalid());
+    }
+    let channel = |index: usize| {
+        hex.get(index..index + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            .ok_or_else(invalid)
+    };
+    Ok(Colour::rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_round_trip() {
+        let red = Style::bold(Colour::black(), Colour::rgb(255, 0, 0));
+        let mut canvas = Canvas::new(Size::new(6, 2));
+        canvas.clear(Style::default());
+        canvas.draw_str(0, 0, Style::default(), "Hello|");
+        canvas.draw_str(0, 1, red, "中!");
+        canvas.draw_str(4, 1, Style::same_colour(Colour::white()), "ab");
+
+        let snapshot = canvas.to_snapshot();
+        assert_eq!(
+            snapshot,
+            "canvas 6x2\n\
+             |Hello||\n\
+             |中! ab|\n\
+             styles\n\
+             |aaaaaa|\n\
+             |b.bacc|\n\
+             a: bg=#000000 fg=#ffffff\n\
+             b: bg=#000000 fg=#ff0000 bold\n\
+             c: bg=#ffffff fg=#ffffff\n"
+        );
+
+        let parsed = Canvas::from_snapshot(&snapshot).unwrap();
+        assert_eq!(parsed.size(), canvas.size());
+        assert_eq!(parsed.buffer(), canvas.buffer());
+    }
+
+    #[test]
+    fn styles_beyond_the_ascii_keys_round_trip() {
+        let num_styles = STYLE_KEYS.len() + 2;
+        let mut canvas = Canvas::new(Size::new(num_styles, 1));
+        for x in 0..num_styles {
+            canvas.draw_str(x, 0, Style::same_colour(Colour::indexed(x as u8)), "x");
+        }
+
+        let snapshot = canvas.to_snapshot();
+        assert!(snapshot.contains("|abc"));
+        assert!(snapshot.contains("~\u{100}\u{101}|\n"));
+        assert!(snapshot.contains("\n\u{101}: bg=colour:82 fg=colour:82\n"));
+
+        let parsed = Canvas::from_snapshot(&snapshot).unwrap();
+        assert_eq!(parsed.buffer(), canvas.buffer());
+    }
+
+    #[test]
+    fn empty_graphemes_are_parsed_as_spaces() {
+        let mut canvas = Canvas::new(Size::new(2, 1));
+        canvas.draw_str(0, 0, Style::default(), "ab");
+        canvas.textel_mut(1, 0).as_mut().unwrap().grapheme = "".into();
+
+        let parsed = Canvas::from_snapshot(&canvas.to_snapshot()).unwrap();
+        assert_eq!(parsed.textel(0, 0), canvas.textel(0, 0));
+        assert_eq!(parsed.textel(1, 0).as_ref().unwrap().grapheme, " ");
+    }
+
+    #[test]
+    fn extended_styles_round_trip() {
+        let style = Style {
+            italic: true,
+            strikethrough: true,
+            underline: true,
+            underline_style: UnderlineStyle::Curly,
+            underline_colour: Colour::rgb(255, 0, 0),
+            ..Style::normal(Colour::indexed(236), Colour::ansi(AnsiColour::BrightCyan))
+        };
+        let description = StyleDescription(&style).to_string();
+        assert_eq!(
+            description,
+            "bg=colour:236 fg=bright-cyan italic strikethrough underline=curly \
+             underline-colour=#ff0000"
+        );
+        assert_eq!(parse_style(&description), Ok(style));
+    }
+
+    #[test]
+    fn snapshot_errors_point_to_the_offending_line() {
+        assert_eq!(
+            Canvas::from_snapshot("canvas 2x1\n|ab|\nstyles\n|az|\na: bg=#000000 fg=#ffffff\n")
+                .unwrap_err(),
+            SnapshotError::new(4, "unknown style key `z`")
+        );
+        assert_eq!(
+            Canvas::from_snapshot("canvas 2x1\n|ab|\nstyles\n|aa|\na: bg=#000000\n").unwrap_err(),
+            SnapshotError::new(5, "missing foreground colour `fg=`")
+        );
+        assert_eq!(
+            Canvas::from_snapshot("canvas 2x1\n|ab|\n").unwrap_err(),
+            SnapshotError::new(
+                3,
+                "unexpected end of snapshot, expected the `styles` separator"
+            )
+        );
+    }
+}
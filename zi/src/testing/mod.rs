@@ -0,0 +1,391 @@
+//! A headless backend for tests, enabled by the `testing` feature and
+//! re-exported by the [`zi-test`](https://docs.rs/zi-test) crate.
+//!
+//! [`TestBackend`](struct.TestBackend.html) owns an [`App`](../app/struct.App.html)
+//! and renders it to an in-memory canvas of a fixed size. Tests script key
+//! presses, resizes and ticks, the backend delivers any messages sent by
+//! components until the application settles, and the drawn canvas can then
+//! be inspected. Tasks spawned by components are run on the test thread while
+//! the application settles.
+pub mod snapshot;
+
+use std::{
+    fmt,
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    app::{App, BlockingTask, ComponentMessage, MessageSender, Task, TaskSpawner},
+    store::Store,
+    terminal::{Canvas, Event, KeyCode, KeyEvent, Size, Style, Textel},
+    Layout,
+};
+
+/// The maximum number of rounds of message delivery and redrawing before the
+/// application is considered to be stuck in a loop.
+const MAX_ROUNDS_UNTIL_IDLE: usize = 10_000;
+
+/// A headless backend that renders an application to an in-memory canvas.
+///
+/// All methods that feed input to the application (`key`, `input`, `resize`,
+/// `tick` etc.) also run the application until it is idle, i.e. until there
+/// are no more pending messages and the canvas is up to date. They return
+/// `&mut Self` so calls can be chained.
+pub struct TestBackend {
+    app: App,
+    receiver: Receiver<ComponentMessage>,
+    tasks: TaskQueue,
+    canvas: Canvas,
+}
+
+impl TestBackend {
+    /// Creates a new backend with a canvas of the given size and renders the
+    /// root layout.
+    pub fn new(size: Size, layout: Layout) -> Self {
+        Self::with_app(size, layout, |_| {})
+    }
+
+    /// Like `new`, but installs a store before the root layout is rendered,
+    /// so components can subscribe to it when created.
+    pub fn with_store(size: Size, layout: Layout, store: Store) -> Self {
+        Self::with_app(size, layout, |app| app.set_store(store))
+    }
+
+    fn with_app(size: Size, layout: Layout, setup: impl FnOnce(&mut App)) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(ChannelMessageSender(sender), size, layout);
+        let tasks = TaskQueue::default();
+        app.set_task_spawner(tasks.clone());
+        setup(&mut app);
+        let mut backend = Self {
+            app,
+            receiver,
+            tasks,
+            canvas: Canvas::new(size),
+        };
+        backend.run_until_idle();
+        backend
+    }
+
+    /// Returns the size of the virtual screen.
+    #[inline]
+    pub fn size(&self) -> Size {
+        self.canvas.size()
+    }
+
+    /// Returns the underlying application runtime.
+    #[inline]
+    pub fn app(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Returns the number of tasks spawned by components which haven't
+    /// completed yet.
+    #[inline]
+    pub fn running_tasks(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Polls every spawned task once, without delivering the messages they
+    /// send. Returns `true` if any of them completed.
+    ///
+    /// This lets a test act between a task completing and its result being
+    /// delivered, which `run_until_idle` does in one go.
+    pub fn poll_tasks(&mut self) -> bool {
+        self.tasks.poll()
+    }
+
+    /// Returns `true` if a component asked the application to exit.
+    #[inline]
+    pub fn has_exited(&self) -> bool {
+        self.app.poll_state().exit()
+    }
+
+    /// Sends an input event to the application.
+    pub fn input(&mut self, event: Event) -> &mut Self {
+        if !self.has_exited() {
+            self.app.handle_input(event);
+        }
+        self.run_until_idle()
+    }
+
+    /// Sends a key press to the application.
+    #[inline]
+    pub fn key(&mut self, key: impl Into<KeyEvent>) -> &mut Self {
+        self.input(Event::Key(key.into()))
+    }
+
+    /// Sends a sequence of key presses to the application, running it until
+    /// idle after each key.
+    pub fn keys(&mut self, keys: impl IntoIterator<Item = impl Into<KeyEvent>>) -> &mut Self {
+        for key in keys {
+            self.key(key);
+        }
+        self
+    }
+
+    /// Types some text, one character key press at a time.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        self.keys(text.chars().map(KeyCode::Char))
+    }
+
+    /// Resizes the virtual screen.
+    pub fn resize(&mut self, size: Size) -> &mut Self {
+        if !self.has_exited() {
+            self.app.handle_resize(size);
+        }
+        self.run_until_idle()
+    }
+
+    /// Ticks all components that were tickable when last drawn. This is the
+    /// equivalent of an animation frame.
+    pub fn tick(&mut self) -> &mut Self {
+        if !self.has_exited() {
+            self.app.tick();
+        }
+        self.run_until_idle()
+    }
+
+    /// Expires pending input as if its timeout had passed, e.g. a key
+    /// sequence waiting for its next key or a bare `Esc` waiting for an `Alt`
+    /// chord.
+    pub fn expire_timeouts(&mut self) -> &mut Self {
+        if let Some(deadline) = self.app.next_deadline() {
+            if !self.has_exited() {
+                self.app.handle_timeout(deadline);
+            }
+        }
+        self.run_until_idle()
+    }
+
+    /// Delivers pending messages and redraws the application until there is
+    /// nothing left to do. Spawned tasks are polled once per round, blocking
+    /// tasks run to completion when first polled.
+    ///
+    /// Panics if the application doesn't settle, e.g. if components keep
+    /// sending messages to each other indefinitely.
+    pub fn run_until_idle(&mut self) -> &mut Self {
+        for _ in 0..MAX_ROUNDS_UNTIL_IDLE {
+            if self.has_exited() {
+                return self;
+            }
+            if self.app.poll_state().dirty() {
+                self.canvas = self.app.draw().clone();
+            }
+
+            let mut idle = !self.tasks.poll();
+            while let Ok(message) = self.receiver.try_recv() {
+                self.app.handle_message(message);
+                idle = false;
+            }
+
+            // Replayed keys are handled one at a time, once the application
+            // settled after the previous one
+            if idle && !self.app.poll_state().dirty() && self.app.has_queued_input() {
+                self.app.handle_queued_input();
+                idle = false;
+            }
+
+            if idle && !self.app.poll_state().dirty() {
+                return self;
+            }
+        }
+        panic!(
+            "application did not become idle after {} rounds of message delivery",
+            MAX_ROUNDS_UNTIL_IDLE
+        );
+    }
+
+    /// Returns the most recently drawn canvas.
+    #[inline]
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Returns the textel at the given position. `None` marks a position
+    /// covered by a wide grapheme to its left.
+    #[inline]
+    pub fn textel(&self, x: usize, y: usize) -> Option<&Textel> {
+        self.canvas.textel(x, y).as_ref()
+    }
+
+    /// Returns the style of the textel at the given position.
+    #[inline]
+    pub fn style(&self, x: usize, y: usize) -> Option<Style> {
+        self.textel(x, y).map(|textel| textel.style)
+    }
+
+    /// Returns the text content of a row of the canvas. Undrawn positions and
+    /// empty graphemes are spaces, so graphemes keep their columns.
+    pub fn line(&self, y: usize) -> String {
+        let width = self.canvas.size().width;
+        let mut line = String::with_capacity(width);
+        // The number of positions covered by the last wide grapheme
+        let mut covered = 0;
+        for textel in &self.canvas.buffer()[y * width..(y + 1) * width] {
+            match textel {
+                Some(textel) if textel.grapheme.is_empty() => line.push(' '),
+                Some(textel) => {
+                    line.push_str(&textel.grapheme);
+                    covered = textel.grapheme.width().saturating_sub(1);
+                    continue;
+                }
+                None if covered > 0 => {}
+                None => line.push(' '),
+            }
+            covered = covered.saturating_sub(1);
+        }
+        line
+    }
+
+    /// Returns the text content of the canvas, one string per row.
+    pub fn lines(&self) -> Vec<String> {
+        (0..self.canvas.size().height)
+            .map(|y| self.line(y))
+            .collect()
+    }
+
+    /// Returns the text content of the canvas, with rows separated by newlines.
+    pub fn text(&self) -> String {
+        self.lines().join("\n")
+    }
+
+    /// Asserts that the text content of the canvas matches the expected
+    /// lines. Trailing whitespace is ignored on both sides.
+    #[track_caller]
+    pub fn assert_lines(&self, expected: &[&str]) {
+        let actual = self.lines();
+        let actual: Vec<_> = actual.iter().map(|line| line.trim_end()).collect();
+        let expected: Vec<_> = expected.iter().map(|line| line.trim_end()).collect();
+        assert!(
+            actual == expected,
+            "canvas content mismatch\n--- expected\n{}\n--- actual\n{}\n",
+            expected.join("\n"),
+            actual.join("\n"),
+        );
+    }
+
+    /// Asserts that the canvas matches a snapshot in the format produced by
+    /// [`Canvas::to_snapshot`](../terminal/struct.Canvas.html#method.to_snapshot).
+    /// On mismatch, the panic message reports the first differing textel and
+    /// its style.
+    #[track_caller]
+    pub fn assert_snapshot(&self, expected: &str) {
+        if let Err(message) = snapshot::compare(&self.canvas, expected) {
+            panic!("{}", message);
+        }
+    }
+
+    /// Asserts that the canvas matches the snapshot stored in a file. Set the
+    /// `ZI_UPDATE_SNAPSHOTS` environment variable to create or update the file
+    /// instead.
+    #[track_caller]
+    pub fn assert_snapshot_file(&self, path: impl AsRef<Path>) {
+        if let Err(message) = snapshot::compare_file(&self.canvas, path.as_ref()) {
+            panic!("{}", message);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ChannelMessageSender(Sender<ComponentMessage>);
+
+impl MessageSender for ChannelMessageSender {
+    fn send(&self, message: ComponentMessage) {
+        // The receiver is dropped together with the application, after which
+        // there is no one left to deliver messages to.
+        let _ = self.0.send(message);
+    }
+
+    fn clone_box(&self) -> Box<dyn MessageSender> {
+        Box::new(self.clone())
+    }
+}
+
+/// Runs spawned tasks on the test thread, whenever the backend runs the
+/// application until idle.
+#[derive(Clone, Default)]
+struct TaskQueue(Arc<Mutex<Vec<Task>>>);
+
+impl TaskQueue {
+    fn len(&self) -> usize {
+        self.0
+            .lock()
+            .expect("task queue lock to not be poisoned")
+            .len()
+    }
+
+    /// Polls every task once. Returns `true` if any of them completed.
+    fn poll(&self) -> bool {
+        // Taken out of the queue, so tasks can spawn other tasks
+        let tasks =
+            std::mem::take(&mut *self.0.lock().expect("task queue lock to not be poisoned"));
+        if tasks.is_empty() {
+            return false;
+        }
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut context = Context::from_waker(&waker);
+        let num_tasks = tasks.len();
+        let pending: Vec<_> = tasks
+            .into_iter()
+            .filter_map(|mut task| match task.as_mut().poll(&mut context) {
+                Poll::Ready(()) => None,
+                Poll::Pending => Some(task),
+            })
+            .collect();
+        let completed = pending.len() < num_tasks;
+        self.0
+            .lock()
+            .expect("task queue lock to not be poisoned")
+            .extend(pending);
+        completed
+    }
+}
+
+impl TaskSpawner for TaskQueue {
+    fn spawn(&self, task: Task) {
+        self.0
+            .lock()
+            .expect("task queue lock to not be poisoned")
+            .push(task);
+    }
+
+    fn spawn_blocking(&self, task: BlockingTask) {
+        self.spawn(Box::pin(async move { task() }));
+    }
+}
+
+impl fmt::Debug for TaskQueue {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "TaskQueue({} tasks)", self.len())
+    }
+}
+
+/// Tasks are polled every round regardless, so wake ups can be ignored.
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_keep_the_columns_of_wide_graphemes_and_undrawn_positions() {
+        let mut canvas = Canvas::new(Size::new(6, 1));
+        canvas.draw_str(0, 0, Style::default(), "界");
+        canvas.draw_str(3, 0, Style::default(), "b");
+        let backend = TestBackend::new(canvas.size(), canvas.into());
+        assert_eq!(backend.textel(1, 0), None);
+        assert_eq!(backend.line(0), "界 b  ");
+    }
+}
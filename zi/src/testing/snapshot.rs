@@ -0,0 +1,196 @@
+//! Comparing canvases against snapshots.
+//!
+//! Snapshots use the text format implemented by
+//! [`Canvas::to_snapshot`](../../terminal/struct.Canvas.html#method.to_snapshot).
+//! When the `ZI_UPDATE_SNAPSHOTS` environment variable is set, snapshot files
+//! that don't match are overwritten with the actual canvas instead of failing.
+
+use crate::terminal::{snapshot::StyleDescription, Canvas, Position, Size, Textel};
+use std::{env, fmt, fs, io, path::Path};
+
+/// Name of the environment variable which turns on updating snapshot files.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "ZI_UPDATE_SNAPSHOTS";
+
+/// The first difference found between two canvases.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// The canvases have different sizes.
+    Size { expected: Size, actual: Size },
+    /// The canvases differ at a position.
+    Textel {
+        position: Position,
+        expected: Option<Textel>,
+        actual: Option<Textel>,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Size { expected, actual } => write!(
+                formatter,
+                "canvas size differs: expected {}x{}, actual {}x{}",
+                expected.width, expected.height, actual.width, actual.height
+            ),
+            Self::Textel {
+                position,
+                expected,
+                actual,
+            } => {
+                writeln!(
+                    formatter,
+                    "first difference at x={} y={}",
+                    position.x, position.y
+                )?;
+                writeln!(formatter, "  expected: {}", TextelDescription(expected))?;
+                write!(formatter, "    actual: {}", TextelDescription(actual))
+            }
+        }
+    }
+}
+
+struct TextelDescription<'a>(&'a Option<Textel>);
+
+impl fmt::Display for TextelDescription<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(textel) => write!(
+                formatter,
+                "{:?} {}",
+                textel.grapheme.as_str(),
+                StyleDescription(&textel.style)
+            ),
+            None => write!(formatter, "<covered by a wide grapheme>"),
+        }
+    }
+}
+
+/// Returns the first position where two canvases differ, in row-major order.
+///
+/// Empty graphemes are considered equal to a space, as that's how they are
+/// presented and how they're written in snapshots.
+pub fn first_difference(expected: &Canvas, actual: &Canvas) -> Option<Difference> {
+    if expected.size() != actual.size() {
+        return Some(Difference::Size {
+            expected: expected.size(),
+            actual: actual.size(),
+        });
+    }
+
+    let width = expected.size().width;
+    expected
+        .buffer()
+        .iter()
+        .zip(actual.buffer())
+        .position(|(expected, actual)| !textels_match(expected, actual))
+        .map(|index| Difference::Textel {
+            position: Position::new(index % width, index / width),
+            expected: expected.buffer()[index].clone(),
+            actual: actual.buffer()[index].clone(),
+        })
+}
+
+/// Compares a canvas against a snapshot, returning a description of the first
+/// difference if they don't match.
+pub fn compare(actual: &Canvas, snapshot: &str) -> Result<(), String> {
+    let expected = Canvas::from_snapshot(snapshot)
+        .map_err(|error| format!("could not parse snapshot: {}", error))?;
+    match first_difference(&expected, actual) {
+        Some(difference) => Err(format!(
+            "canvas doesn't match snapshot, {}\n--- expected\n{}--- actual\n{}",
+            difference,
+            snapshot,
+            actual.to_snapshot()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Compares a canvas against a snapshot file. If `ZI_UPDATE_SNAPSHOTS` is set,
+/// a missing or mismatched file is written with the actual canvas instead.
+pub fn compare_file(actual: &Canvas, path: &Path) -> Result<(), String> {
+    let update = env::var_os(UPDATE_SNAPSHOTS_VAR).is_some();
+    let result = match fs::read_to_string(path) {
+        Ok(snapshot) => compare(actual, &snapshot),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Err(format!(
+            "snapshot file {} does not exist, set {}=1 to create it",
+            path.display(),
+            UPDATE_SNAPSHOTS_VAR
+        )),
+        Err(error) => return Err(format!("could not read {}: {}", path.display(), error)),
+    };
+
+    match result {
+        Err(_) if update => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|error| format!("could not create {}: {}", parent.display(), error))?;
+            }
+            fs::write(path, actual.to_snapshot())
+                .map_err(|error| format!("could not write {}: {}", path.display(), error))
+        }
+        result => result,
+    }
+}
+
+#[inline]
+fn textels_match(expected: &Option<Textel>, actual: &Option<Textel>) -> bool {
+    match (expected, actual) {
+        (Some(expected), Some(actual)) => {
+            expected.style == actual.style && grapheme(expected) == grapheme(actual)
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[inline]
+fn grapheme(textel: &Textel) -> &str {
+    if textel.grapheme.is_empty() {
+        " "
+    } else {
+        textel.grapheme.as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::{Colour, Style};
+
+    #[test]
+    fn reports_first_differing_textel() {
+        let mut expected = Canvas::new(Size::new(3, 2));
+        expected.draw_str(0, 1, Style::default(), "ab");
+        let mut actual = expected.clone();
+        let red = Style::normal(Colour::black(), Colour::rgb(255, 0, 0));
+        actual.draw_str(1, 1, red, "b");
+        actual.draw_str(2, 1, red, "c");
+
+        assert_eq!(first_difference(&expected, &expected.clone()), None);
+        assert_eq!(
+            first_difference(&expected, &actual),
+            Some(Difference::Textel {
+                position: Position::new(1, 1),
+                expected: Some(Textel {
+                    grapheme: "b".into(),
+                    style: Style::default()
+                }),
+                actual: Some(Textel {
+                    grapheme: "b".into(),
+                    style: red
+                }),
+            })
+        );
+
+        // Empty graphemes are written as spaces in snapshots
+        assert_eq!(compare(&expected, &expected.to_snapshot()), Ok(()));
+        let error = compare(&actual, &expected.to_snapshot()).unwrap_err();
+        assert!(error.contains("first difference at x=1 y=1"), "{}", error);
+        assert!(
+            error.contains("actual: \"b\" bg=#000000 fg=#ff0000"),
+            "{}",
+            error
+        );
+    }
+}
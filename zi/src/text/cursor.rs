@@ -149,6 +149,11 @@ impl Cursor {
         ensure_trailing_newline_with_content(text);
     }
 
+    pub fn insert_str<'a>(&mut self, text: &mut impl TextStorageMut<'a>, string: &str) {
+        text.insert(self.range.start, string);
+        ensure_trailing_newline_with_content(text);
+    }
+
     pub fn insert_chars<'a>(
         &mut self,
         text: &mut impl TextStorageMut<'a>,
@@ -29,6 +29,7 @@ pub trait TextStorage<'a> {
 
 pub trait TextStorageMut<'a>: TextStorage<'a> {
     fn insert_char(&mut self, char_index: CharIndex, character: char);
+    fn insert(&mut self, char_index: CharIndex, text: &str);
     fn remove(&mut self, range: impl RangeBounds<usize>);
 }
 
@@ -64,6 +64,10 @@ impl<'a> TextStorageMut<'a> for Rope {
         Rope::insert_char(self, char_index.0, character);
     }
 
+    fn insert(&mut self, char_index: CharIndex, text: &str) {
+        Rope::insert(self, char_index.0, text);
+    }
+
     fn remove(&mut self, range: impl RangeBounds<usize>) {
         Rope::remove(self, range);
     }
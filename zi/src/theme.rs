@@ -0,0 +1,205 @@
+//! Themes map semantic style tokens, e.g. `input.cursor`, to styles.
+//!
+//! The built-in components look up their styles in the active theme, unless
+//! they are given a style in their properties. The active theme is set with
+//! [`App::set_theme`](../app/struct.App.html#method.set_theme), or at runtime
+//! by any component with
+//! [`ComponentLink::set_theme`](../struct.ComponentLink.html#method.set_theme).
+//! Switching themes re-renders all mounted components. Components read the
+//! active theme with
+//! [`ComponentLink::theme`](../struct.ComponentLink.html#method.theme),
+//! typically in `view`.
+//!
+//! The tokens used by the built-in components are
+//!
+//! | Token                   | Used for                                          |
+//! |-------------------------|---------------------------------------------------|
+//! | `input.content`         | The text of an `Input`                            |
+//! | `input.cursor`          | The grapheme under the cursor of an `Input`       |
+//! | `border`                | The stroke of a `Border`                          |
+//! | `border.focused`        | The stroke of a `Border` set as focused           |
+//! | `select.background`     | The space below the items of a `Select`           |
+//! | `select.item`           | Items of a `Select`, e.g. in the command palette  |
+//! | `select.selected`       | The selected item of a `Select`                   |
+//! | `command-palette.key`   | The key bindings listed by the command palette    |
+//! | `key-hints`             | The command names listed by `KeyHints`            |
+//! | `key-hints.key`         | The keys listed by `KeyHints`                     |
+//! | `scroll-view.scrollbar` | The scrollbar of a `ScrollView`                   |
+//!
+//! Applications are free to add their own tokens.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use crate::terminal::{Colour, Style};
+
+/// A mapping from style tokens to styles.
+///
+/// ```
+/// # use zi::{theme::Theme, Colour, Style};
+/// let theme = Theme::dark().set("input.cursor", Style::normal(Colour::white(), Colour::black()));
+/// assert_eq!(
+///     theme.style("input.cursor"),
+///     Style::normal(Colour::white(), Colour::black())
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    styles: HashMap<Cow<'static, str>, Style>,
+}
+
+impl Theme {
+    /// Creates a theme without any styles.
+    pub fn new() -> Self {
+        Self {
+            styles: HashMap::new(),
+        }
+    }
+
+    /// The default theme, with light text on a dark background.
+    pub fn dark() -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const GRAY_245: Colour = Colour::rgb(146, 131, 116);
+        const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
+
+        Self::new()
+            .set("input.content", Style::normal(DARK0_SOFT, LIGHT2))
+            .set("input.cursor", Style::normal(BRIGHT_BLUE, DARK0_SOFT))
+            .set("border", Style::default())
+            .set(
+                "border.focused",
+                Style::normal(Colour::black(), BRIGHT_BLUE),
+            )
+            .set("select.background", Style::normal(DARK0_SOFT, LIGHT2))
+            .set("select.item", Style::normal(DARK0_SOFT, LIGHT2))
+            .set("select.selected", Style::normal(BRIGHT_BLUE, DARK0_SOFT))
+            .set("command-palette.key", Style::normal(DARK0_SOFT, GRAY_245))
+            .set("key-hints", Style::default())
+            .set("key-hints.key", Style::default())
+            .set("scroll-view.scrollbar", Style::normal(DARK0_SOFT, GRAY_245))
+    }
+
+    /// A theme with dark text on a light background.
+    pub fn light() -> Self {
+        const LIGHT0_SOFT: Colour = Colour::rgb(242, 229, 188);
+        const DARK2: Colour = Colour::rgb(80, 73, 69);
+        const GRAY_244: Colour = Colour::rgb(146, 131, 116);
+        const FADED_BLUE: Colour = Colour::rgb(7, 102, 120);
+
+        Self::new()
+            .set("input.content", Style::normal(LIGHT0_SOFT, DARK2))
+            .set("input.cursor", Style::normal(FADED_BLUE, LIGHT0_SOFT))
+            .set("border", Style::default().invert())
+            .set("border.focused", Style::normal(Colour::white(), FADED_BLUE))
+            .set("select.background", Style::normal(LIGHT0_SOFT, DARK2))
+            .set("select.item", Style::normal(LIGHT0_SOFT, DARK2))
+            .set("select.selected", Style::normal(FADED_BLUE, LIGHT0_SOFT))
+            .set("command-palette.key", Style::normal(LIGHT0_SOFT, GRAY_244))
+            .set("key-hints", Style::default().invert())
+            .set("key-hints.key", Style::default().invert())
+            .set(
+                "scroll-view.scrollbar",
+                Style::normal(LIGHT0_SOFT, GRAY_244),
+            )
+    }
+
+    /// Sets the style of a token, replacing its current style.
+    pub fn set(mut self, token: impl Into<Cow<'static, str>>, style: Style) -> Self {
+        self.insert(token, style);
+        self
+    }
+
+    /// Sets the style of a token, replacing its current style.
+    pub fn insert(&mut self, token: impl Into<Cow<'static, str>>, style: Style) {
+        self.styles.insert(token.into(), style);
+    }
+
+    /// Returns the style of a token, or `None` if the theme doesn't have it.
+    pub fn get(&self, token: &str) -> Option<Style> {
+        self.styles.get(token).copied()
+    }
+
+    /// Returns the style of a token, or the default style if the theme
+    /// doesn't have it.
+    pub fn style(&self, token: &str) -> Style {
+        self.get(token).unwrap_or_default()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// The active theme of an app, shared with the links of its components.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SharedTheme(Arc<RwLock<Arc<Theme>>>);
+
+impl SharedTheme {
+    pub(crate) fn get(&self) -> Arc<Theme> {
+        self.0
+            .read()
+            .expect("theme lock to not be poisoned")
+            .clone()
+    }
+
+    pub(crate) fn set(&self, theme: Theme) {
+        *self.0.write().expect("theme lock to not be poisoned") = Arc::new(theme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::{
+            border::{Border, BorderProperties},
+            input::{Cursor, Input, InputProperties},
+            text::{Text, TextProperties},
+        },
+        terminal::{Colour, Size},
+        testing::TestBackend,
+        ComponentExt, Item, Layout,
+    };
+
+    #[test]
+    fn switching_themes_restyles_components() {
+        let input = || {
+            Input::with(InputProperties {
+                style: None,
+                content: "ab\n".into(),
+                cursor: Cursor::new(),
+                on_change: None,
+                focused: Some(false),
+            })
+        };
+        let text = || Text::with(TextProperties::new().content("c"));
+        let overridden = Style::normal(Colour::black(), Colour::rgb(255, 0, 0));
+        let mut app = TestBackend::new(
+            Size::new(6, 6),
+            Layout::column([
+                Item::fixed(3)(Border::with(BorderProperties::new(input))),
+                Item::fixed(3)(Border::with(BorderProperties::new(text).style(overridden))),
+            ]),
+        );
+
+        let dark = Theme::dark();
+        assert_eq!(app.style(0, 0), Some(dark.style("border")));
+        assert_eq!(app.style(2, 1), Some(dark.style("input.content")));
+        assert_eq!(app.style(1, 1), Some(dark.style("input.cursor")));
+        assert_eq!(app.style(0, 3), Some(overridden));
+
+        let light = Theme::light();
+        app.app().set_theme(light.clone());
+        app.run_until_idle();
+        assert_eq!(app.style(0, 0), Some(light.style("border")));
+        assert_eq!(app.style(2, 1), Some(light.style("input.content")));
+        assert_eq!(app.style(1, 1), Some(light.style("input.cursor")));
+        assert_eq!(app.style(0, 3), Some(overridden));
+    }
+}